@@ -0,0 +1,95 @@
+use crate::error::{Result, WeatherError};
+
+use std::fmt;
+
+/// Location the weather is requested for.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Address {
+    /// Place name to be geocoded, e.g. `Kyiv` or `Paris, FR`
+    Name(String),
+
+    /// Coordinates in degrees, no geocoding needed
+    Coordinates { latitude: f64, longitude: f64 },
+
+    /// Postal code with optional ISO 3166 country code
+    PostalCode {
+        code: String,
+        country: Option<String>,
+    },
+
+    /// Provider-native location identifier, e.g. AccuWeather location key
+    Id(String),
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Address::Name(name) => write!(f, "{name}"),
+            Address::Coordinates {
+                latitude,
+                longitude,
+            } => write!(f, "{latitude},{longitude}"),
+            Address::PostalCode { code, country } => match country {
+                Some(country) => write!(f, "{code},{country}"),
+                None => write!(f, "{code}"),
+            },
+            Address::Id(id) => write!(f, "id {id}"),
+        }
+    }
+}
+
+/// Parses the user supplied address.
+///
+/// `latitude,longitude` pairs in degrees (e.g. `50.45,30.52`) are coordinates,
+/// anything else is a place name.
+pub fn parse_address(value: &str) -> Result<Address> {
+    let value = value.trim();
+
+    let coordinates = value
+        .split_once(',')
+        .and_then(|(lat, lon)| Some((lat.trim().parse().ok()?, lon.trim().parse().ok()?)));
+
+    match coordinates {
+        Some((latitude, longitude)) => {
+            check_coordinates(latitude, longitude)?;
+            Ok(Address::Coordinates {
+                latitude,
+                longitude,
+            })
+        }
+        None => Ok(Address::Name(value.to_string())),
+    }
+}
+
+/// Parses postal code with optional country code, e.g. `02000,UA`.
+pub fn parse_postal_code(value: &str) -> Result<Address> {
+    let (code, country) = match value.split_once(',') {
+        Some((code, country)) => (code.trim(), Some(country.trim())),
+        None => (value.trim(), None),
+    };
+
+    if code.is_empty() {
+        return Err(WeatherError::Parse(format!(
+            "Invalid postal code '{value}'"
+        )));
+    }
+
+    Ok(Address::PostalCode {
+        code: code.to_string(),
+        country: country.filter(|e| !e.is_empty()).map(|e| e.to_uppercase()),
+    })
+}
+
+fn check_coordinates(latitude: f64, longitude: f64) -> Result<()> {
+    if !(-90.0..=90.0).contains(&latitude) {
+        return Err(WeatherError::Parse(format!(
+            "Latitude {latitude} is out of range (-90..90)"
+        )));
+    }
+    if !(-180.0..=180.0).contains(&longitude) {
+        return Err(WeatherError::Parse(format!(
+            "Longitude {longitude} is out of range (-180..180)"
+        )));
+    }
+    Ok(())
+}
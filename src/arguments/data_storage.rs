@@ -1,17 +1,53 @@
-use crate::arguments::WeatherProvider;
-use std::error::Error;
+use crate::arguments::{GeocoderConfig, ProviderGroup, SavedLocation, WeatherProvider};
+use crate::error::Result;
 
 /// Arguments data storage.
 pub trait DataStorage {
     /// Get all configured providers.
-    fn get_all_providers(&self) -> Result<Vec<WeatherProvider>, Box<dyn Error>>;
+    fn get_all_providers(&self) -> Result<Vec<WeatherProvider>>;
 
     /// Get provider by name.
-    fn get_provider(&self, name: &str) -> Result<WeatherProvider, Box<dyn Error>>;
+    fn get_provider(&self, name: &str) -> Result<WeatherProvider>;
 
     /// Add a new provider.
-    fn add_provider(&self, provider: &WeatherProvider) -> Result<(), Box<dyn Error>>;
+    fn add_provider(&self, provider: &WeatherProvider) -> Result<()>;
 
     /// Remove the provider by name.
-    fn remove_provider(&self, name: &str) -> Result<(), Box<dyn Error>>;
+    fn remove_provider(&self, name: &str) -> Result<()>;
+
+    /// Get all provider groups.
+    fn get_all_groups(&self) -> Result<Vec<ProviderGroup>>;
+
+    /// Get provider group by name.
+    fn get_group(&self, name: &str) -> Result<ProviderGroup>;
+
+    /// Add a new provider group.
+    fn add_group(&self, group: &ProviderGroup) -> Result<()>;
+
+    /// Remove the provider group by name.
+    fn remove_group(&self, name: &str) -> Result<()>;
+
+    /// Get the default provider or provider group name, if configured.
+    fn get_default_provider(&self) -> Result<Option<String>>;
+
+    /// Set the existing provider or provider group as the default one.
+    fn set_default_provider(&self, name: &str) -> Result<()>;
+
+    /// Get all saved locations.
+    fn get_all_locations(&self) -> Result<Vec<SavedLocation>>;
+
+    /// Get saved location by name.
+    fn get_location(&self, name: &str) -> Result<SavedLocation>;
+
+    /// Add a new saved location.
+    fn add_location(&self, location: &SavedLocation) -> Result<()>;
+
+    /// Remove the saved location by name.
+    fn remove_location(&self, name: &str) -> Result<()>;
+
+    /// Get the geocoder configuration, the provider one if not configured.
+    fn get_geocoder(&self) -> Result<GeocoderConfig>;
+
+    /// Set the geocoder configuration.
+    fn set_geocoder(&self, geocoder: &GeocoderConfig) -> Result<()>;
 }
@@ -0,0 +1,96 @@
+use crate::error::{Result, WeatherError};
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc};
+
+/// Naive timestamp formats accepted in addition to RFC 3339. Interpreted as UTC.
+static NAIVE_DATE_TIME_FORMATS: [&str; 4] = [
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%d %H:%M",
+];
+
+/// Parses the user supplied date relative to `now`.
+///
+/// Supported forms:
+/// * `now`, `today`, `tomorrow`, `yesterday`;
+/// * relative offsets like `+3d`, `+12h`, `-30m` or `+1w`;
+/// * ISO-8601 timestamps (`2023-02-14T15:00:00+02:00`, `2023-02-14T15:00` is UTC);
+/// * absolute dates (`2023-02-14`) referring to the midday UTC of that day,
+///   or to `now` if the date is today.
+pub fn parse_date(value: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    let value = value.trim();
+
+    match value.to_lowercase().as_str() {
+        "now" | "today" => return Ok(now),
+        "tomorrow" => return Ok(now + Duration::days(1)),
+        "yesterday" => return Ok(now - Duration::days(1)),
+        _ => {}
+    }
+
+    if value.starts_with('+') || value.starts_with('-') {
+        let offset = parse_offset(value)?;
+        return now.checked_add_signed(offset).ok_or_else(|| {
+            WeatherError::Parse(format!("Relative date '{value}' is out of range"))
+        });
+    }
+
+    if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+        return Ok(date.with_timezone(&Utc));
+    }
+
+    for format in NAIVE_DATE_TIME_FORMATS {
+        if let Ok(date) = NaiveDateTime::parse_from_str(value, format) {
+            return Ok(Utc.from_utc_datetime(&date));
+        }
+    }
+
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        if date == now.date_naive() {
+            return Ok(now);
+        }
+        if let Some(midday) = date.and_hms_opt(12, 0, 0) {
+            return Ok(Utc.from_utc_datetime(&midday));
+        }
+    }
+
+    Err(WeatherError::Parse(format!(
+        "Unrecognized date '{value}' (expected e.g. 2023-02-14, 2023-02-14T15:00:00Z, tomorrow or +3d)"
+    )))
+}
+
+/// Offsets are limited to this many minutes, about a thousand years either way.
+const MAX_OFFSET_MINUTES: i64 = 1000 * 366 * 24 * 60;
+
+/// Parses relative offsets like `+3d` or `-12h`.
+fn parse_offset(value: &str) -> Result<Duration> {
+    let (sign, rest) = value.split_at(1);
+    let unit_position = rest
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(|| WeatherError::Parse(format!("Missing unit in relative date '{value}'")))?;
+    let (amount, unit) = rest.split_at(unit_position);
+
+    let amount = amount.parse::<i64>().map_err(|e| {
+        WeatherError::Parse(format!("Invalid amount in relative date '{value}' ({e})"))
+    })?;
+    let amount = if sign == "-" { -amount } else { amount };
+
+    let unit_minutes = match unit {
+        "m" | "min" => 1,
+        "h" => 60,
+        "d" => 24 * 60,
+        "w" => 7 * 24 * 60,
+        _ => {
+            return Err(WeatherError::Parse(format!(
+                "Unknown unit '{unit}' in relative date '{value}' (expected m, h, d or w)"
+            )))
+        }
+    };
+
+    match amount.checked_mul(unit_minutes) {
+        Some(minutes) if minutes.abs() <= MAX_OFFSET_MINUTES => Ok(Duration::minutes(minutes)),
+        _ => Err(WeatherError::Parse(format!(
+            "Relative date '{value}' is too far from now"
+        ))),
+    }
+}
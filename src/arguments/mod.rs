@@ -1,17 +1,30 @@
 extern crate clap;
 extern crate serde;
-extern crate string_error;
 
+mod address;
 mod data_storage;
+mod date;
 mod production_data_storage;
 
+pub use address::{parse_address, parse_postal_code, Address};
 pub use data_storage::DataStorage;
+pub use date::parse_date;
 pub use production_data_storage::ProductionDataStorage;
 
+use crate::cache::CacheMode;
+use crate::error::{Result, WeatherError};
+use crate::geocoder::Location;
+use crate::output::{Output, OutputFormat, Template};
+use crate::provider::units::{
+    LengthUnit, PressureUnit, SpeedUnit, TemperatureUnit, UnitSystem, Units,
+};
+
 use clap::{Args, Parser, Subcommand, ValueEnum};
 use serde::{Deserialize, Serialize};
 
 use std::fmt;
+use std::fs;
+use std::path::PathBuf;
 
 /// Root CLI node.
 #[derive(Parser)]
@@ -27,20 +40,294 @@ pub enum WeatherCommand {
     /// Configure credentials for the weather provider.
     Provider(ProviderCommand),
 
+    /// Configure groups of providers tried in turn.
+    Group(GroupCommand),
+
+    /// Configure saved locations.
+    Location(LocationCommand),
+
+    /// Configure how addresses are resolved to locations.
+    Geocoder(GeocoderCommand),
+
+    /// Inspect and clear cached responses.
+    Cache(CacheCommand),
+
     /// Show the weather for the provided address.
     Get {
-        /// City to get the weather for
-        #[clap(forbid_empty_values = true)]
-        address: String,
+        #[clap(flatten)]
+        address: AddressArgs,
 
-        /// Date  to get the weather for (default is current time)
-        #[clap(short, long, forbid_empty_values = true)]
+        /// Date to get the weather for, e.g. 2023-02-14, 2023-02-14T15:00:00Z, tomorrow or +3d
+        /// (default is current time, past dates show observed weather)
+        #[clap(short, long, forbid_empty_values = true, allow_hyphen_values = true)]
         date: Option<String>,
 
-        /// Weather provider (default configured is used if not specified)
+        /// Weather provider or provider group (default configured is used if not specified)
         #[clap(short, long, forbid_empty_values = true)]
-        provider_name: String,
+        provider_name: Option<String>,
+
+        #[clap(flatten)]
+        units: UnitsArgs,
+
+        #[clap(flatten)]
+        output: OutputArgs,
+
+        #[clap(flatten)]
+        cache: CacheArgs,
     },
+
+    /// Show the weather forecast for the provided address.
+    Forecast {
+        #[clap(flatten)]
+        address: AddressArgs,
+
+        /// Number of days to forecast
+        #[clap(short, long, default_value_t = 5, value_parser = clap::value_parser!(u32).range(1..))]
+        days: u32,
+
+        /// Show hourly forecast instead of daily one
+        #[clap(long)]
+        hourly: bool,
+
+        /// Weather provider or provider group (default configured is used if not specified)
+        #[clap(short, long, forbid_empty_values = true)]
+        provider_name: Option<String>,
+
+        #[clap(flatten)]
+        units: UnitsArgs,
+
+        #[clap(flatten)]
+        output: OutputArgs,
+
+        #[clap(flatten)]
+        cache: CacheArgs,
+    },
+
+    /// Compare the weather for the provided address across providers.
+    Compare {
+        #[clap(flatten)]
+        address: AddressArgs,
+
+        /// Date to get the weather for, e.g. 2023-02-14, 2023-02-14T15:00:00Z, tomorrow or +3d
+        /// (default is current time, past dates show observed weather)
+        #[clap(short, long, forbid_empty_values = true, allow_hyphen_values = true)]
+        date: Option<String>,
+
+        /// Comma-separated providers or provider groups to compare
+        /// (all configured providers are compared if not specified)
+        #[clap(short, long, value_delimiter = ',', forbid_empty_values = true)]
+        providers: Vec<String>,
+
+        #[clap(flatten)]
+        units: UnitsArgs,
+    },
+}
+
+/// Address options.
+#[derive(Args, Default)]
+pub struct AddressArgs {
+    /// Saved location name, city, optionally with country (e.g. 'Paris, FR'),
+    /// or coordinates as 'latitude,longitude'
+    #[clap(
+        forbid_empty_values = true,
+        required_unless_present_any = &["coords", "zip", "location-id"],
+        conflicts_with_all = &["coords", "zip", "location-id"]
+    )]
+    pub address: Option<String>,
+
+    /// Coordinates as 'latitude,longitude', e.g. -33.87,151.21
+    #[clap(
+        long,
+        forbid_empty_values = true,
+        allow_hyphen_values = true,
+        conflicts_with_all = &["zip", "location-id"]
+    )]
+    pub coords: Option<String>,
+
+    /// Postal code with optional country code, e.g. 02000,UA
+    #[clap(long, forbid_empty_values = true, conflicts_with = "location-id")]
+    pub zip: Option<String>,
+
+    /// Provider-native location identifier (AccuWeather location key, OpenWeather city ID)
+    #[clap(long, forbid_empty_values = true)]
+    pub location_id: Option<String>,
+
+    /// Country code or name to narrow down ambiguous locations, e.g. US
+    #[clap(long, forbid_empty_values = true)]
+    pub country: Option<String>,
+
+    /// State or region to narrow down ambiguous locations, e.g. Illinois
+    #[clap(long, forbid_empty_values = true)]
+    pub region: Option<String>,
+}
+
+impl From<&str> for AddressArgs {
+    fn from(address: &str) -> Self {
+        Self {
+            address: Some(address.to_string()),
+            ..Self::default()
+        }
+    }
+}
+
+impl AddressArgs {
+    /// Parses the address specified in any of the supported forms.
+    pub fn address(&self) -> Result<Address> {
+        if let Some(address) = &self.address {
+            return parse_address(address);
+        }
+        if let Some(coords) = &self.coords {
+            return match parse_address(coords)? {
+                Address::Name(_) => Err(WeatherError::Parse(format!(
+                    "Invalid coordinates '{coords}'"
+                ))),
+                address => Ok(address),
+            };
+        }
+        if let Some(zip) = &self.zip {
+            return parse_postal_code(zip);
+        }
+        match &self.location_id {
+            Some(id) => Ok(Address::Id(id.clone())),
+            None => Err(WeatherError::Parse("No address specified".to_string())),
+        }
+    }
+}
+
+/// Display units options.
+#[derive(Args, Default)]
+pub struct UnitsArgs {
+    /// Unit system to display values in
+    #[clap(long, value_enum, default_value = "metric")]
+    pub units: UnitSystem,
+
+    /// Temperature unit overriding the unit system one
+    #[clap(long, value_enum)]
+    pub temperature_unit: Option<TemperatureUnit>,
+
+    /// Pressure unit overriding the unit system one
+    #[clap(long, value_enum)]
+    pub pressure_unit: Option<PressureUnit>,
+
+    /// Wind speed unit overriding the unit system one
+    #[clap(long, value_enum)]
+    pub speed_unit: Option<SpeedUnit>,
+
+    /// Precipitation unit overriding the unit system one
+    #[clap(long, value_enum)]
+    pub precipitation_unit: Option<LengthUnit>,
+
+    /// Visibility unit overriding the unit system one
+    #[clap(long, value_enum)]
+    pub distance_unit: Option<LengthUnit>,
+}
+
+impl UnitsArgs {
+    /// Makes display units from the unit system and overrides.
+    pub fn units(&self) -> Units {
+        let mut units = Units::from(self.units);
+
+        if let Some(unit) = self.temperature_unit {
+            units.temperature = unit;
+        }
+        if let Some(unit) = self.pressure_unit {
+            units.pressure = unit;
+        }
+        if let Some(unit) = self.speed_unit {
+            units.speed = unit;
+        }
+        if let Some(unit) = self.precipitation_unit {
+            units.precipitation = unit;
+        }
+        if let Some(unit) = self.distance_unit {
+            units.distance = unit;
+        }
+
+        units
+    }
+}
+
+/// Output options.
+#[derive(Args)]
+pub struct OutputArgs {
+    /// Output format
+    #[clap(short, long, value_enum, default_value = "text")]
+    pub output: OutputFormat,
+
+    /// Output template, e.g. '{description}: {temperature:.1}°C, wind {wind_speed} m/s'
+    #[clap(long, conflicts_with_all = &["output", "format-file"])]
+    pub format: Option<String>,
+
+    /// File to read the output template from
+    #[clap(long, value_name = "PATH", conflicts_with = "output")]
+    pub format_file: Option<PathBuf>,
+
+    /// Text shown in templates for values not provided
+    #[clap(long, default_value = "unknown")]
+    pub placeholder: String,
+}
+
+impl Default for OutputArgs {
+    fn default() -> Self {
+        Self {
+            output: OutputFormat::default(),
+            format: None,
+            format_file: None,
+            placeholder: "unknown".to_string(),
+        }
+    }
+}
+
+impl OutputArgs {
+    /// Makes output settings, reading and parsing the template if any.
+    pub fn output(&self) -> Result<Output> {
+        let template = match (&self.format, &self.format_file) {
+            (Some(template), _) => template.clone(),
+            (None, Some(path)) => fs::read_to_string(path).map_err(|e| {
+                WeatherError::Config(format!(
+                    "Failed to read template file '{}' ({e})",
+                    path.display()
+                ))
+            })?,
+            (None, None) => return Ok(Output::Format(self.output)),
+        };
+
+        Ok(Output::Template {
+            template: Template::parse(&template)?,
+            placeholder: self.placeholder.clone(),
+        })
+    }
+}
+
+/// Cache options.
+#[derive(Args, Default)]
+pub struct CacheArgs {
+    /// Neither use nor store cached responses
+    #[clap(long, conflicts_with_all = &["refresh", "offline"])]
+    pub no_cache: bool,
+
+    /// Ignore cached responses and store fresh ones
+    #[clap(long, conflicts_with = "offline")]
+    pub refresh: bool,
+
+    /// Show the last stored data without making any requests, even if expired
+    #[clap(long)]
+    pub offline: bool,
+}
+
+impl CacheArgs {
+    /// Gets how cached responses are used.
+    pub fn mode(&self) -> CacheMode {
+        if self.no_cache {
+            CacheMode::Bypass
+        } else if self.offline {
+            CacheMode::Offline
+        } else if self.refresh {
+            CacheMode::Refresh
+        } else {
+            CacheMode::Use
+        }
+    }
 }
 
 /// Provider configuration node.
@@ -69,6 +356,207 @@ pub enum ProviderSubcommand {
         #[clap(short, long, forbid_empty_values = true)]
         name: Option<String>,
     },
+
+    /// Set default weather provider.
+    Default {
+        /// Provider name.
+        #[clap(short, long, forbid_empty_values = true)]
+        name: String,
+    },
+}
+
+/// Provider group configuration node.
+#[derive(Args)]
+pub struct GroupCommand {
+    #[clap(subcommand)]
+    pub command: GroupSubcommand,
+}
+
+/// Provider group configuration commands.
+#[derive(Subcommand)]
+pub enum GroupSubcommand {
+    /// Add group of providers, usable in place of a provider name.
+    Add(ProviderGroup),
+
+    /// Remove provider group.
+    Remove {
+        /// Group name
+        #[clap(forbid_empty_values = true)]
+        name: String,
+    },
+
+    /// Show provider group.
+    Show {
+        /// Group name, all groups are shown if not specified
+        #[clap(forbid_empty_values = true)]
+        name: Option<String>,
+    },
+}
+
+/// Provider group representation.
+#[derive(Clone, Debug, PartialEq, Args, Serialize, Deserialize)]
+pub struct ProviderGroup {
+    /// Group name
+    #[clap(forbid_empty_values = true)]
+    pub name: String,
+
+    /// Providers tried in turn if the previous one cannot be reached, fails
+    /// or runs out of quota, e.g. owm,accu
+    #[clap(
+        short,
+        long,
+        required = true,
+        value_delimiter = ',',
+        forbid_empty_values = true
+    )]
+    pub providers: Vec<String>,
+}
+
+impl fmt::Display for ProviderGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Name: {}\nProviders: {}",
+            self.name,
+            self.providers.join(", ")
+        )
+    }
+}
+
+/// Location configuration node.
+#[derive(Args)]
+pub struct LocationCommand {
+    #[clap(subcommand)]
+    pub command: LocationSubcommand,
+}
+
+/// Location configuration commands.
+#[derive(Subcommand)]
+pub enum LocationSubcommand {
+    /// Save location under the name, geocoding the address with the provider if needed.
+    Add {
+        /// Location name
+        #[clap(forbid_empty_values = true)]
+        name: String,
+
+        #[clap(flatten)]
+        address: AddressArgs,
+
+        /// Weather provider used for the location if not specified
+        #[clap(short, long, forbid_empty_values = true)]
+        provider: Option<String>,
+    },
+
+    /// Remove saved location.
+    Remove {
+        /// Location name
+        #[clap(forbid_empty_values = true)]
+        name: String,
+    },
+
+    /// Show saved location.
+    Show {
+        /// Location name
+        #[clap(forbid_empty_values = true)]
+        name: String,
+    },
+
+    /// List saved locations.
+    List,
+}
+
+/// Saved location representation.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SavedLocation {
+    /// Location name
+    pub name: String,
+
+    /// Resolved location
+    pub location: Location,
+
+    /// Weather provider used for the location if not specified
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+}
+
+impl fmt::Display for SavedLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Name: {}\nLocation: {}", self.name, self.location)?;
+        if let Some(provider) = &self.provider {
+            write!(f, "\nProvider: {provider}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Geocoder configuration node.
+#[derive(Args)]
+pub struct GeocoderCommand {
+    #[clap(subcommand)]
+    pub command: GeocoderSubcommand,
+}
+
+/// Geocoder configuration commands.
+#[derive(Subcommand)]
+pub enum GeocoderSubcommand {
+    /// Resolve addresses with the weather provider geocoding API (default).
+    Provider,
+
+    /// Resolve addresses offline with GeoNames cities dump.
+    Geonames {
+        /// Path to the cities dump (e.g. cities15000.txt), admin1CodesASCII.txt next to it
+        /// is used for region names
+        #[clap(long, value_name = "PATH")]
+        path: PathBuf,
+    },
+
+    /// Show configured geocoder.
+    Show,
+}
+
+/// Geocoder configuration.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum GeocoderConfig {
+    /// Weather provider geocoding API
+    #[default]
+    Provider,
+
+    /// Offline GeoNames cities dump
+    GeoNames { path: PathBuf },
+}
+
+impl fmt::Display for GeocoderConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GeocoderConfig::Provider => write!(f, "Geocoder: weather provider"),
+            GeocoderConfig::GeoNames { path } => {
+                write!(f, "Geocoder: GeoNames\nPath: {}", path.display())
+            }
+        }
+    }
+}
+
+/// Cache node.
+#[derive(Args)]
+pub struct CacheCommand {
+    #[clap(subcommand)]
+    pub command: CacheSubcommand,
+}
+
+/// Cache commands.
+#[derive(Subcommand)]
+pub enum CacheSubcommand {
+    /// Show cache usage statistics.
+    Stats,
+
+    /// Remove all cached responses.
+    Clear {
+        /// Remove only the responses cached for the provider or group, along with the location
+        /// keys and forecasts the provider keeps for all providers of its kind
+        #[clap(short, long, forbid_empty_values = true)]
+        provider: Option<String>,
+    },
 }
 
 /// Provider representation.
@@ -83,8 +571,14 @@ pub struct WeatherProvider {
     pub provider: Provider,
 
     #[clap(short, long, forbid_empty_values = true)]
-    /// Provider API key
-    pub api_key: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Provider API key, optional for keyless providers
+    pub api_key: Option<String>,
+
+    #[clap(short, long, forbid_empty_values = true)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Contact sent along with requests, such as an email address, required by MET Norway
+    pub contact: Option<String>,
 }
 
 /// Supported providers.
@@ -95,15 +589,58 @@ pub enum Provider {
 
     /// AccuWeather
     AccuWeather,
+
+    /// Open-Meteo, no API key required
+    OpenMeteo,
+
+    /// US National Weather Service, no API key required
+    Nws,
+
+    /// MET Norway, no API key required but a contact
+    MetNorway,
+
+    /// WeatherAPI.com
+    WeatherApi,
+}
+
+impl Provider {
+    /// Checks whether the provider cannot be used without an API key.
+    pub fn requires_api_key(&self) -> bool {
+        match self {
+            Provider::OpenWeather | Provider::AccuWeather | Provider::WeatherApi => true,
+            Provider::OpenMeteo | Provider::Nws | Provider::MetNorway => false,
+        }
+    }
+
+    /// Checks whether the provider cannot be used without a contact.
+    pub fn requires_contact(&self) -> bool {
+        matches!(self, Provider::MetNorway)
+    }
+
+    /// Scope of the data the provider keeps in the cache on its own, shared by all configured
+    /// providers of the kind.
+    pub fn cache_scope(&self) -> &'static str {
+        match self {
+            Provider::OpenWeather => "open-weather",
+            Provider::AccuWeather => "accu-weather",
+            Provider::OpenMeteo => "open-meteo",
+            Provider::Nws => "nws",
+            Provider::MetNorway => "met-norway",
+            Provider::WeatherApi => "weather-api",
+        }
+    }
 }
 
 impl fmt::Display for WeatherProvider {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "Name: {}\nProvider: {}\nAPI key: {}",
-            self.name, self.provider, self.api_key
-        )
+        write!(f, "Name: {}\nProvider: {}", self.name, self.provider)?;
+        if let Some(api_key) = &self.api_key {
+            write!(f, "\nAPI key: {api_key}")?;
+        }
+        if let Some(contact) = &self.contact {
+            write!(f, "\nContact: {contact}")?;
+        }
+        Ok(())
     }
 }
 
@@ -115,6 +652,10 @@ impl fmt::Display for Provider {
             match self {
                 Provider::OpenWeather => "OpenWeather",
                 Provider::AccuWeather => "AccuWeather",
+                Provider::OpenMeteo => "Open-Meteo",
+                Provider::Nws => "NWS",
+                Provider::MetNorway => "MET Norway",
+                Provider::WeatherApi => "WeatherAPI.com",
             }
         )
     }
@@ -1,12 +1,43 @@
-use crate::arguments::{data_storage, WeatherProvider};
+use crate::arguments::{
+    data_storage, GeocoderConfig, ProviderGroup, SavedLocation, WeatherProvider,
+};
+use crate::error::{Result, WeatherError};
+
+use serde::{Deserialize, Serialize};
 
 use std::env;
-use std::error::Error;
 use std::fs::OpenOptions;
 use std::io;
 use std::path::PathBuf;
 
-use string_error::into_err;
+/// Persisted configuration.
+#[derive(Default, Serialize, Deserialize)]
+struct Config {
+    #[serde(default)]
+    providers: Vec<WeatherProvider>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    groups: Vec<ProviderGroup>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    default_provider: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    locations: Vec<SavedLocation>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    geocoder: Option<GeocoderConfig>,
+}
+
+/// Persisted configuration layouts.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredConfig {
+    /// Initial layout holding the providers list only.
+    Providers(Vec<WeatherProvider>),
+
+    Config(Config),
+}
 
 fn get_config_path() -> PathBuf {
     let mut path = match home::home_dir() {
@@ -18,90 +49,206 @@ fn get_config_path() -> PathBuf {
     path
 }
 
-fn save_providers(providers: Vec<WeatherProvider>) -> Result<(), Box<dyn Error>> {
+fn load_config() -> Result<Config> {
+    let file = match OpenOptions::new().read(true).open(get_config_path()) {
+        Ok(file) => file,
+        Err(e) => {
+            if e.kind() == io::ErrorKind::NotFound {
+                // Absence of the config means empty config.
+                return Ok(Config::default());
+            }
+            return Err(WeatherError::Config(format!(
+                "Failed to open providers file while reading config ({e})"
+            )));
+        }
+    };
+
+    let config: StoredConfig = match serde_json::from_reader(&file) {
+        Ok(config) => config,
+        Err(e) => {
+            if e.is_eof() {
+                // Empty config file equals to empty config.
+                return Ok(Config::default());
+            } else if e.is_data() || e.is_syntax() {
+                return Err(WeatherError::Config(format!(
+                    "Broken config data/syntax ({e})"
+                )));
+            } else {
+                return Err(WeatherError::Config(format!(
+                    "Failed to parse providers config ({e})"
+                )));
+            }
+        }
+    };
+
+    match config {
+        StoredConfig::Providers(providers) => Ok(Config {
+            providers,
+            ..Config::default()
+        }),
+        StoredConfig::Config(config) => Ok(config),
+    }
+}
+
+fn save_config(config: &Config) -> Result<()> {
     let file = OpenOptions::new()
         .create(true)
         .write(true)
         .truncate(true)
         .open(get_config_path())
         .map_err(|e| {
-            into_err(format!(
+            WeatherError::Config(format!(
                 "Failed to open providers file while saving config ({e})"
             ))
         })?;
 
-    let value = serde_json::to_value(providers)
-        .map_err(|e| into_err(format!("Failed to serialize providers config ({e})")))?;
+    let value = serde_json::to_value(config)
+        .map_err(|e| WeatherError::Config(format!("Failed to serialize providers config ({e})")))?;
 
     serde_json::to_writer(&file, &value)
-        .map_err(|e| into_err(format!("Failed to save providers config ({e})")))
+        .map_err(|e| WeatherError::Config(format!("Failed to save providers config ({e})")))
 }
 
 /// Data storage to be used in production
 pub struct ProductionDataStorage;
 
 impl data_storage::DataStorage for ProductionDataStorage {
-    fn get_all_providers(&self) -> Result<Vec<WeatherProvider>, Box<dyn Error>> {
-        let file = match OpenOptions::new().read(true).open(get_config_path()) {
-            Ok(file) => file,
-            Err(e) => {
-                if e.kind() == io::ErrorKind::NotFound {
-                    // Absence of the config means empty config.
-                    return Ok(Vec::<WeatherProvider>::new());
-                }
-                return Err(into_err(format!(
-                    "Failed to open providers file while reading config ({e})"
-                )));
-            }
-        };
-
-        let providers: Vec<WeatherProvider> = match serde_json::from_reader(&file) {
-            Ok(providers) => providers,
-            Err(e) => {
-                if e.is_eof() {
-                    // Empty config file equals to empty config.
-                    return Ok(Vec::<WeatherProvider>::new());
-                } else if e.is_data() || e.is_syntax() {
-                    return Err(into_err(format!("Broken config data/syntax ({e})")));
-                } else {
-                    return Err(into_err(format!("Failed to parse providers config ({e})")));
-                }
-            }
-        };
-
-        Ok(providers)
+    fn get_all_providers(&self) -> Result<Vec<WeatherProvider>> {
+        load_config().map(|config| config.providers)
     }
 
-    fn get_provider(&self, name: &str) -> Result<WeatherProvider, Box<dyn Error>> {
+    fn get_provider(&self, name: &str) -> Result<WeatherProvider> {
         match self.get_all_providers() {
             Ok(mut providers) => {
                 if let Some(position) = providers.iter().position(|e| e.name == *name) {
                     return Ok(providers.swap_remove(position));
                 }
-                return Err(into_err("Not found".to_string()));
+                Err(WeatherError::NotFound("Not found".to_string()))
             }
-            Err(e) => Err(into_err(format!("Failed to get providers list ({e})"))),
+            Err(e) => Err(e.context("Failed to get providers list")),
         }
     }
 
-    fn add_provider(&self, provider: &WeatherProvider) -> Result<(), Box<dyn Error>> {
-        let mut providers = self
-            .get_all_providers()
-            .map_err(|e| into_err(format!("Failed to get providers list ({e})")))?;
+    fn add_provider(&self, provider: &WeatherProvider) -> Result<()> {
+        let mut config = load_config().map_err(|e| e.context("Failed to get providers list"))?;
 
-        providers.push(provider.clone());
-        save_providers(providers)
+        config.providers.push(provider.clone());
+        save_config(&config)
     }
-    fn remove_provider(&self, name: &str) -> Result<(), Box<dyn Error>> {
-        let mut providers = self
-            .get_all_providers()
-            .map_err(|e| into_err(format!("Failed to get providers list ({e})")))?;
 
-        match providers.iter().position(|e| e.name == *name) {
-            Some(pos) => providers.remove(pos),
-            None => return Err(into_err("Not found".to_string())),
+    fn remove_provider(&self, name: &str) -> Result<()> {
+        let mut config = load_config().map_err(|e| e.context("Failed to get providers list"))?;
+
+        match config.providers.iter().position(|e| e.name == *name) {
+            Some(pos) => config.providers.remove(pos),
+            None => return Err(WeatherError::NotFound("Not found".to_string())),
         };
 
-        save_providers(providers)
+        if config.default_provider.as_deref() == Some(name) {
+            config.default_provider = None;
+        }
+
+        save_config(&config)
+    }
+
+    fn get_all_groups(&self) -> Result<Vec<ProviderGroup>> {
+        load_config().map(|config| config.groups)
+    }
+
+    fn get_group(&self, name: &str) -> Result<ProviderGroup> {
+        let mut groups = self
+            .get_all_groups()
+            .map_err(|e| e.context("Failed to get groups list"))?;
+
+        match groups.iter().position(|e| e.name == *name) {
+            Some(position) => Ok(groups.swap_remove(position)),
+            None => Err(WeatherError::NotFound("Not found".to_string())),
+        }
+    }
+
+    fn add_group(&self, group: &ProviderGroup) -> Result<()> {
+        let mut config = load_config().map_err(|e| e.context("Failed to get groups list"))?;
+
+        config.groups.push(group.clone());
+        save_config(&config)
+    }
+
+    fn remove_group(&self, name: &str) -> Result<()> {
+        let mut config = load_config().map_err(|e| e.context("Failed to get groups list"))?;
+
+        match config.groups.iter().position(|e| e.name == *name) {
+            Some(pos) => config.groups.remove(pos),
+            None => return Err(WeatherError::NotFound("Not found".to_string())),
+        };
+
+        if config.default_provider.as_deref() == Some(name) {
+            config.default_provider = None;
+        }
+
+        save_config(&config)
+    }
+
+    fn get_default_provider(&self) -> Result<Option<String>> {
+        load_config().map(|config| config.default_provider)
+    }
+
+    fn set_default_provider(&self, name: &str) -> Result<()> {
+        let mut config = load_config().map_err(|e| e.context("Failed to get providers list"))?;
+
+        if !config.providers.iter().any(|e| e.name == *name)
+            && !config.groups.iter().any(|e| e.name == *name)
+        {
+            return Err(WeatherError::NotFound("Not found".to_string()));
+        }
+
+        config.default_provider = Some(name.to_string());
+        save_config(&config)
+    }
+
+    fn get_all_locations(&self) -> Result<Vec<SavedLocation>> {
+        load_config().map(|config| config.locations)
+    }
+
+    fn get_location(&self, name: &str) -> Result<SavedLocation> {
+        let mut locations = self
+            .get_all_locations()
+            .map_err(|e| e.context("Failed to get locations list"))?;
+
+        match locations.iter().position(|e| e.name == *name) {
+            Some(position) => Ok(locations.swap_remove(position)),
+            None => Err(WeatherError::NotFound("Not found".to_string())),
+        }
+    }
+
+    fn add_location(&self, location: &SavedLocation) -> Result<()> {
+        let mut config = load_config().map_err(|e| e.context("Failed to get locations list"))?;
+
+        config.locations.push(location.clone());
+        save_config(&config)
+    }
+
+    fn remove_location(&self, name: &str) -> Result<()> {
+        let mut config = load_config().map_err(|e| e.context("Failed to get locations list"))?;
+
+        match config.locations.iter().position(|e| e.name == *name) {
+            Some(pos) => config.locations.remove(pos),
+            None => return Err(WeatherError::NotFound("Not found".to_string())),
+        };
+
+        save_config(&config)
+    }
+
+    fn get_geocoder(&self) -> Result<GeocoderConfig> {
+        load_config().map(|config| config.geocoder.unwrap_or_default())
+    }
+
+    fn set_geocoder(&self, geocoder: &GeocoderConfig) -> Result<()> {
+        let mut config = load_config().map_err(|e| e.context("Failed to get geocoder config"))?;
+
+        config.geocoder = match geocoder {
+            GeocoderConfig::Provider => None,
+            geocoder => Some(geocoder.clone()),
+        };
+        save_config(&config)
     }
 }
@@ -0,0 +1,65 @@
+use crate::cache::{CacheStats, CachedValue};
+use crate::error::Result;
+
+use chrono::Duration;
+use serde_json::Value;
+
+use std::sync::Arc;
+
+/// Cached responses storage.
+///
+/// Shared between the caching wrappers and the providers keeping responses of their own,
+/// including the ones queried concurrently.
+pub trait CacheStorage: Send + Sync {
+    /// Get the unexpired value stored under the key, counting hits and misses.
+    fn get(&self, key: &str) -> Result<Option<Value>>;
+
+    /// Get the last value stored under the key, even if expired.
+    fn get_last(&self, key: &str) -> Result<Option<CachedValue>>;
+
+    /// Store the value under the key for the time to live, entries expired for longer than
+    /// they are retained for offline use are dropped.
+    fn put(&self, key: &str, value: &Value, ttl: Duration) -> Result<()>;
+
+    /// Remove the entry stored under the key, if any.
+    fn remove(&self, key: &str) -> Result<()>;
+
+    /// Get cache usage statistics.
+    fn stats(&self) -> Result<CacheStats>;
+
+    /// Remove all entries and reset statistics, returns the number of entries removed.
+    fn clear(&self) -> Result<usize>;
+
+    /// Remove the entries of the scope, returns the number of entries removed.
+    fn clear_scope(&self, scope: &str) -> Result<usize>;
+}
+
+impl<S: CacheStorage + ?Sized> CacheStorage for Arc<S> {
+    fn get(&self, key: &str) -> Result<Option<Value>> {
+        (**self).get(key)
+    }
+
+    fn get_last(&self, key: &str) -> Result<Option<CachedValue>> {
+        (**self).get_last(key)
+    }
+
+    fn put(&self, key: &str, value: &Value, ttl: Duration) -> Result<()> {
+        (**self).put(key, value, ttl)
+    }
+
+    fn remove(&self, key: &str) -> Result<()> {
+        (**self).remove(key)
+    }
+
+    fn stats(&self) -> Result<CacheStats> {
+        (**self).stats()
+    }
+
+    fn clear(&self) -> Result<usize> {
+        (**self).clear()
+    }
+
+    fn clear_scope(&self, scope: &str) -> Result<usize> {
+        (**self).clear_scope(scope)
+    }
+}
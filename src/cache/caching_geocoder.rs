@@ -0,0 +1,65 @@
+use crate::cache::{self, CacheMode, CacheStorage, ResponseCache};
+use crate::error::Result;
+use crate::geocoder::{Geocoder, Location};
+
+/// Geocoder caching results of the wrapped one.
+///
+/// Results are keyed by the geocoder name, query type and the query.
+pub struct CachingGeocoder {
+    geocoder: Box<dyn Geocoder>,
+    geocoder_name: String,
+    cache: ResponseCache,
+}
+
+impl CachingGeocoder {
+    pub fn new(
+        geocoder: Box<dyn Geocoder>,
+        geocoder_name: &str,
+        storage: Box<dyn CacheStorage>,
+        mode: CacheMode,
+    ) -> Self {
+        Self {
+            geocoder,
+            geocoder_name: geocoder_name.to_string(),
+            cache: ResponseCache::new(storage, mode),
+        }
+    }
+
+    fn key(&self, query: &str, value: &str) -> String {
+        format!("{query}|{}|{}", self.geocoder_name, value.to_lowercase())
+    }
+}
+
+impl Geocoder for CachingGeocoder {
+    fn search(&self, address: &str) -> Result<Vec<Location>> {
+        self.cache
+            .get_or_fetch(&self.key("search", address), cache::location_ttl(), || {
+                self.geocoder.search(address)
+            })
+    }
+
+    fn search_postal_code(&self, code: &str, country: Option<&str>) -> Result<Vec<Location>> {
+        let query = format!("{code},{}", country.unwrap_or_default());
+
+        self.cache
+            .get_or_fetch(&self.key("zip", &query), cache::location_ttl(), || {
+                self.geocoder.search_postal_code(code, country)
+            })
+    }
+
+    fn reverse(&self, latitude: f64, longitude: f64) -> Result<Option<Location>> {
+        let query = format!("{latitude:.4},{longitude:.4}");
+
+        self.cache
+            .get_or_fetch(&self.key("reverse", &query), cache::location_ttl(), || {
+                self.geocoder.reverse(latitude, longitude)
+            })
+    }
+
+    fn get_location(&self, id: &str) -> Result<Location> {
+        self.cache
+            .get_or_fetch(&self.key("location", id), cache::location_ttl(), || {
+                self.geocoder.get_location(id)
+            })
+    }
+}
@@ -0,0 +1,210 @@
+use crate::cache::{self, CacheMode, CacheStorage, ResponseCache};
+use crate::error::Result;
+use crate::geocoder::Location;
+use crate::provider::units::{Length, Pressure, Speed, Temperature};
+use crate::provider::{Forecast, ForecastEntry, Provider, WeatherInfo};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use std::cell::RefCell;
+
+/// Weather info as cached, values are kept unconverted so that any units share entries.
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "WeatherInfo")]
+struct WeatherInfoDef {
+    description: Option<String>,
+    temperature: Option<Temperature>,
+    humidity: Option<i64>,
+    pressure: Option<Pressure>,
+    feels_like: Option<Temperature>,
+    dew_point: Option<Temperature>,
+    wind_speed: Option<Speed>,
+    wind_direction: Option<i64>,
+    wind_gust: Option<Speed>,
+    precipitation: Option<Length>,
+    precipitation_probability: Option<i64>,
+    cloud_cover: Option<i64>,
+    visibility: Option<Length>,
+    uv_index: Option<f64>,
+    sunrise: Option<DateTime<Utc>>,
+    sunset: Option<DateTime<Utc>>,
+    air_quality: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedWeather(#[serde(with = "WeatherInfoDef")] WeatherInfo);
+
+#[derive(Serialize, Deserialize)]
+struct CachedForecastEntry {
+    time: DateTime<Utc>,
+
+    #[serde(with = "WeatherInfoDef")]
+    weather: WeatherInfo,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedForecast(Vec<CachedForecastEntry>);
+
+impl From<Forecast> for CachedForecast {
+    fn from(forecast: Forecast) -> Self {
+        Self(
+            forecast
+                .entries
+                .into_iter()
+                .map(|e| CachedForecastEntry {
+                    time: e.time,
+                    weather: e.weather,
+                })
+                .collect(),
+        )
+    }
+}
+
+impl From<CachedForecast> for Forecast {
+    fn from(forecast: CachedForecast) -> Self {
+        Self {
+            entries: forecast
+                .0
+                .into_iter()
+                .map(|e| ForecastEntry {
+                    time: e.time,
+                    weather: e.weather,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Response cached along with the provider answered it, if known.
+#[derive(Serialize, Deserialize)]
+struct Answered<T> {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    provider: Option<String>,
+
+    response: T,
+}
+
+/// Provider caching responses of the wrapped one.
+///
+/// Responses are keyed by the provider name, query type and location, dates are
+/// truncated to the hour as forecasts are hourly at best. Expired responses served
+/// offline are reported by `stale_since`. The provider answered is cached along with
+/// the response, so that it is reported by `answered` for cached responses as well.
+pub struct CachingProvider<P> {
+    provider: P,
+    provider_name: String,
+    cache: ResponseCache,
+
+    /// Name of the provider answered the last request
+    answered: RefCell<Option<String>>,
+}
+
+impl<P: Provider> CachingProvider<P> {
+    pub fn new(
+        provider: P,
+        provider_name: &str,
+        storage: Box<dyn CacheStorage>,
+        mode: CacheMode,
+    ) -> Self {
+        Self {
+            provider,
+            provider_name: provider_name.to_string(),
+            cache: ResponseCache::new(storage, mode),
+            answered: RefCell::new(None),
+        }
+    }
+
+    /// Gets the wrapped provider.
+    pub fn provider(&self) -> &P {
+        &self.provider
+    }
+
+    /// Time the oldest expired response served was stored, if any was served.
+    pub fn stale_since(&self) -> Option<DateTime<Utc>> {
+        self.cache.stale_since.get()
+    }
+
+    /// Gets the cached response, fetching it along with the provider answered otherwise.
+    fn get_or_fetch<T: Serialize + DeserializeOwned>(
+        &self,
+        key: &str,
+        ttl: Duration,
+        fetch: impl FnOnce() -> Result<T>,
+    ) -> Result<T> {
+        let answered = self.cache.get_or_fetch(key, ttl, || {
+            Ok(Answered {
+                response: fetch()?,
+                provider: self.provider.answered(),
+            })
+        })?;
+
+        self.answered.replace(answered.provider);
+        Ok(answered.response)
+    }
+
+    fn key(&self, query: &str, location: &Location) -> String {
+        let mut key = format!(
+            "{query}|{}|{:.4},{:.4}",
+            self.provider_name, location.latitude, location.longitude
+        );
+        if let Some(location_key) = &location.key {
+            key.push_str(&format!("|{location_key}"));
+        }
+        key
+    }
+}
+
+impl<P: Provider> Provider for CachingProvider<P> {
+    fn get_weather(&self, location: &Location, date: Option<DateTime<Utc>>) -> Result<WeatherInfo> {
+        let (key, ttl) = match date {
+            Some(date) => (
+                format!(
+                    "{}|{}",
+                    self.key("weather", location),
+                    date.format("%Y-%m-%dT%H")
+                ),
+                cache::forecast_ttl(),
+            ),
+            None => (self.key("weather", location), cache::current_ttl()),
+        };
+
+        self.get_or_fetch(&key, ttl, || {
+            self.provider.get_weather(location, date).map(CachedWeather)
+        })
+        .map(|e| e.0)
+    }
+
+    fn get_forecast(&self, location: &Location, days: u32, hourly: bool) -> Result<Forecast> {
+        let key = format!(
+            "{}|{days}|{}",
+            self.key("forecast", location),
+            if hourly { "hourly" } else { "daily" }
+        );
+
+        self.get_or_fetch(&key, cache::forecast_ttl(), || {
+            self.provider
+                .get_forecast(location, days, hourly)
+                .map(CachedForecast::from)
+        })
+        .map(Forecast::from)
+    }
+
+    fn get_history(&self, location: &Location, date: DateTime<Utc>) -> Result<WeatherInfo> {
+        let key = format!(
+            "{}|{}",
+            self.key("history", location),
+            date.format("%Y-%m-%dT%H")
+        );
+
+        self.get_or_fetch(&key, cache::history_ttl(), || {
+            self.provider.get_history(location, date).map(CachedWeather)
+        })
+        .map(|e| e.0)
+    }
+
+    fn answered(&self) -> Option<String> {
+        self.answered.borrow().clone()
+    }
+}
@@ -0,0 +1,184 @@
+mod cache_storage;
+mod caching_geocoder;
+mod caching_provider;
+mod production_cache_storage;
+
+pub use cache_storage::CacheStorage;
+pub use caching_geocoder::CachingGeocoder;
+pub use caching_provider::CachingProvider;
+pub use production_cache_storage::ProductionCacheStorage;
+
+use crate::error::{Result, WeatherError};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use std::cell::Cell;
+use std::fmt;
+
+/// Time to live of current conditions.
+pub fn current_ttl() -> Duration {
+    Duration::minutes(10)
+}
+
+/// Time to live of forecasts, including the forecast for a specific date.
+pub fn forecast_ttl() -> Duration {
+    Duration::hours(1)
+}
+
+/// Time to live of observed past weather, which hardly ever changes.
+pub fn history_ttl() -> Duration {
+    Duration::days(30)
+}
+
+/// Time to live of geocoding results.
+pub fn location_ttl() -> Duration {
+    Duration::weeks(4)
+}
+
+/// Time expired responses are kept for to be served offline.
+pub fn stale_retention() -> Duration {
+    Duration::days(7)
+}
+
+/// Scope of the cached entry, the provider or geocoder name its key is qualified with.
+///
+/// Keys are made of the query, the scope and the query parameters separated with `|`.
+pub fn key_scope(key: &str) -> Option<&str> {
+    key.split('|').nth(1)
+}
+
+/// How cached responses are used.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CacheMode {
+    /// Cached responses are returned while fresh, new ones are stored
+    #[default]
+    Use,
+
+    /// Cached responses are ignored, new ones are stored
+    Refresh,
+
+    /// Cache is neither read nor written
+    Bypass,
+
+    /// Nothing is fetched, cached responses are returned even if expired
+    Offline,
+}
+
+/// Value stored in the cache.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CachedValue {
+    pub value: serde_json::Value,
+
+    /// Time the value was stored
+    pub stored_at: DateTime<Utc>,
+}
+
+/// Cache usage statistics.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Number of stored entries, expired ones included
+    pub entries: usize,
+
+    /// Number of expired entries
+    pub expired: usize,
+
+    /// Number of lookups served from the cache
+    pub hits: u64,
+
+    /// Number of lookups not found in the cache or expired
+    pub misses: u64,
+
+    /// Cache size in bytes
+    pub size: u64,
+
+    /// Time the oldest entry was stored
+    pub oldest: Option<DateTime<Utc>>,
+}
+
+impl fmt::Display for CacheStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Entries: {} ({} expired)\nHits: {}\nMisses: {}\nSize: {} bytes",
+            self.entries, self.expired, self.hits, self.misses, self.size
+        )?;
+        if let Some(oldest) = self.oldest {
+            write!(f, "\nOldest: {}", oldest.format("%Y-%m-%d %H:%M UTC"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Response cache shared by caching wrappers.
+///
+/// Cache failures are never fatal, responses are fetched as if nothing was cached.
+/// Expired responses are served offline and if the request could not be delivered.
+struct ResponseCache {
+    storage: Box<dyn CacheStorage>,
+    mode: CacheMode,
+
+    /// Time the oldest expired response served was stored
+    stale_since: Cell<Option<DateTime<Utc>>>,
+}
+
+impl ResponseCache {
+    fn new(storage: Box<dyn CacheStorage>, mode: CacheMode) -> Self {
+        Self {
+            storage,
+            mode,
+            stale_since: Cell::new(None),
+        }
+    }
+
+    /// Gets the cached response, fetching and storing it if missing or expired.
+    fn get_or_fetch<T: Serialize + DeserializeOwned>(
+        &self,
+        key: &str,
+        ttl: Duration,
+        fetch: impl FnOnce() -> Result<T>,
+    ) -> Result<T> {
+        if let CacheMode::Use | CacheMode::Offline = self.mode {
+            let cached = self.storage.get(key).ok().flatten();
+            if let Some(value) = cached.and_then(|e| serde_json::from_value(e).ok()) {
+                return Ok(value);
+            }
+        }
+
+        if self.mode == CacheMode::Offline {
+            return self.get_stale(key).ok_or_else(|| {
+                WeatherError::NotFound("No data stored for offline use".to_string())
+            });
+        }
+
+        let value = match fetch() {
+            Ok(value) => value,
+            Err(e @ WeatherError::Network(_)) if self.mode != CacheMode::Bypass => {
+                return self.get_stale(key).ok_or(e)
+            }
+            Err(e) => return Err(e),
+        };
+
+        if self.mode != CacheMode::Bypass {
+            if let Ok(json) = serde_json::to_value(&value) {
+                let _ = self.storage.put(key, &json, ttl);
+            }
+        }
+        Ok(value)
+    }
+
+    /// Gets the last stored response regardless of its expiry, noting its age.
+    fn get_stale<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let cached = self.storage.get_last(key).ok().flatten()?;
+        let value = serde_json::from_value(cached.value).ok()?;
+
+        let stale_since = match self.stale_since.get() {
+            Some(stale_since) => stale_since.min(cached.stored_at),
+            None => cached.stored_at,
+        };
+        self.stale_since.set(Some(stale_since));
+
+        Some(value)
+    }
+}
@@ -0,0 +1,246 @@
+use crate::cache::{self, CacheStats, CacheStorage, CachedValue};
+use crate::error::{Result, WeatherError};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use std::collections::HashMap;
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+/// Cached value.
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    stored_at: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+    value: Value,
+}
+
+/// Persisted cache.
+#[derive(Default, Serialize, Deserialize)]
+struct Cache {
+    #[serde(default)]
+    entries: HashMap<String, Entry>,
+
+    #[serde(default)]
+    hits: u64,
+
+    #[serde(default)]
+    misses: u64,
+}
+
+fn get_cache_path() -> PathBuf {
+    let mut path = match home::home_dir() {
+        Some(path) => path,
+        None => env::temp_dir(),
+    };
+
+    path.push(".weather_cache.data");
+    path
+}
+
+fn load_cache() -> Result<Cache> {
+    let file = match OpenOptions::new().read(true).open(get_cache_path()) {
+        Ok(file) => file,
+        Err(e) => {
+            if e.kind() == io::ErrorKind::NotFound {
+                // Absence of the cache means empty cache.
+                return Ok(Cache::default());
+            }
+            return Err(WeatherError::Config(format!(
+                "Failed to open cache file ({e})"
+            )));
+        }
+    };
+
+    serde_json::from_reader(&file).map_err(|e| {
+        WeatherError::Config(format!(
+            "Cache file is corrupted ({e}), run `weather cache clear` to reset it"
+        ))
+    })
+}
+
+/// Locks the cache for modification until the returned file is dropped.
+///
+/// Modifications read the whole cache and save it back, so concurrent ones, made by other
+/// processes or by threads with storages of their own, would otherwise drop each other's entries.
+/// Readers need no lock, as the cache file is replaced at once.
+fn lock_cache() -> Result<File> {
+    let mut path = get_cache_path().into_os_string();
+    path.push(".lock");
+
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)
+        .map_err(|e| WeatherError::Config(format!("Failed to open cache lock file ({e})")))?;
+    file.lock()
+        .map_err(|e| WeatherError::Config(format!("Failed to lock cache ({e})")))?;
+    Ok(file)
+}
+
+/// Sequence number of the temporary files written by the process.
+static TEMP_FILE_SEQUENCE: AtomicUsize = AtomicUsize::new(0);
+
+fn save_cache(cache: &Cache) -> Result<()> {
+    let path = get_cache_path();
+    let mut temp_path = path.clone().into_os_string();
+    temp_path.push(format!(
+        ".{}.{}.tmp",
+        process::id(),
+        TEMP_FILE_SEQUENCE.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    // Other processes and threads read the cache concurrently, so it is replaced at once
+    // instead of being rewritten in place.
+    let write = || -> io::Result<()> {
+        let mut file = OpenOptions::new()
+            .create_new(true)
+            .write(true)
+            .open(&temp_path)?;
+        serde_json::to_writer(&file, cache)?;
+        file.flush()?;
+        fs::rename(&temp_path, &path)
+    };
+
+    write().map_err(|e| {
+        let _ = fs::remove_file(&temp_path);
+        WeatherError::Config(format!("Failed to save cache ({e})"))
+    })
+}
+
+/// Cache storage to be used in production, kept in a file in the home directory.
+///
+/// Hits and misses are counted in memory and added to the stored ones once the storage is
+/// dropped, so lookups do not rewrite the file.
+#[derive(Default)]
+pub struct ProductionCacheStorage {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl Drop for ProductionCacheStorage {
+    fn drop(&mut self) {
+        let (hits, misses) = (*self.hits.get_mut(), *self.misses.get_mut());
+        if hits == 0 && misses == 0 {
+            return;
+        }
+
+        // Statistics are not worth failing for.
+        if let Ok(_lock) = lock_cache() {
+            if let Ok(mut cache) = load_cache() {
+                cache.hits += hits;
+                cache.misses += misses;
+                let _ = save_cache(&cache);
+            }
+        }
+    }
+}
+
+impl CacheStorage for ProductionCacheStorage {
+    fn get(&self, key: &str) -> Result<Option<Value>> {
+        let value = load_cache()?
+            .entries
+            .remove(key)
+            .filter(|e| e.expires_at > Utc::now())
+            .map(|e| e.value);
+        match value {
+            Some(_) => self.hits.fetch_add(1, Ordering::Relaxed),
+            None => self.misses.fetch_add(1, Ordering::Relaxed),
+        };
+
+        Ok(value)
+    }
+
+    fn get_last(&self, key: &str) -> Result<Option<CachedValue>> {
+        Ok(load_cache()?.entries.remove(key).map(|e| CachedValue {
+            value: e.value,
+            stored_at: e.stored_at,
+        }))
+    }
+
+    fn put(&self, key: &str, value: &Value, ttl: Duration) -> Result<()> {
+        let _lock = lock_cache()?;
+        let mut cache = load_cache()?;
+        let now = Utc::now();
+
+        cache
+            .entries
+            .retain(|_, e| e.expires_at + cache::stale_retention() > now);
+        cache.entries.insert(
+            key.to_string(),
+            Entry {
+                stored_at: now,
+                expires_at: now + ttl,
+                value: value.clone(),
+            },
+        );
+
+        save_cache(&cache)
+    }
+
+    fn remove(&self, key: &str) -> Result<()> {
+        let _lock = lock_cache()?;
+        let mut cache = load_cache()?;
+
+        if cache.entries.remove(key).is_some() {
+            save_cache(&cache)?;
+        }
+        Ok(())
+    }
+
+    fn stats(&self) -> Result<CacheStats> {
+        let cache = load_cache()?;
+        let now = Utc::now();
+
+        Ok(CacheStats {
+            entries: cache.entries.len(),
+            expired: cache
+                .entries
+                .values()
+                .filter(|e| e.expires_at <= now)
+                .count(),
+            hits: cache.hits + self.hits.load(Ordering::Relaxed),
+            misses: cache.misses + self.misses.load(Ordering::Relaxed),
+            size: fs::metadata(get_cache_path()).map_or(0, |e| e.len()),
+            oldest: cache.entries.values().map(|e| e.stored_at).min(),
+        })
+    }
+
+    fn clear(&self) -> Result<usize> {
+        let _lock = lock_cache()?;
+        // Corrupted cache is cleared as well.
+        let entries = load_cache().map_or(0, |e| e.entries.len());
+        self.hits.store(0, Ordering::Relaxed);
+        self.misses.store(0, Ordering::Relaxed);
+
+        match fs::remove_file(get_cache_path()) {
+            Ok(()) => Ok(entries),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(entries),
+            Err(e) => Err(WeatherError::Config(format!(
+                "Failed to remove cache file ({e})"
+            ))),
+        }
+    }
+
+    fn clear_scope(&self, scope: &str) -> Result<usize> {
+        let _lock = lock_cache()?;
+        let mut cache = load_cache()?;
+        let entries = cache.entries.len();
+
+        cache
+            .entries
+            .retain(|key, _| cache::key_scope(key) != Some(scope));
+        let removed = entries - cache.entries.len();
+        if removed > 0 {
+            save_cache(&cache)?;
+        }
+        Ok(removed)
+    }
+}
@@ -0,0 +1,102 @@
+use reqwest::StatusCode;
+
+use std::fmt;
+
+/// Result of the weather operations.
+pub type Result<T, E = WeatherError> = std::result::Result<T, E>;
+
+/// Weather error, the kind tells what went wrong and the exit code to report.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WeatherError {
+    /// Configuration or local data is missing, invalid or cannot be stored
+    Config(String),
+
+    /// Requested provider, group, location or data does not exist
+    NotFound(String),
+
+    /// Provider could not be reached or failed to answer
+    Network(String),
+
+    /// Provider rejected the credentials
+    Auth(String),
+
+    /// Provider request quota is exhausted
+    Quota(String),
+
+    /// Input or provider response could not be parsed, or the request was rejected as invalid
+    Parse(String),
+
+    /// Requested data or operation is not supported
+    Unsupported(String),
+}
+
+impl WeatherError {
+    /// Makes error of the kind the unsuccessful HTTP status stands for.
+    pub fn from_status(status: StatusCode) -> Self {
+        let message = format!("Request failed ({status})");
+        match status {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => Self::Auth(message),
+            StatusCode::TOO_MANY_REQUESTS => Self::Quota(message),
+            StatusCode::NOT_FOUND => Self::NotFound(message),
+            // Other rejected requests are malformed, such as ones with invalid parameters.
+            status if status.is_client_error() => Self::Parse(message),
+            _ => Self::Network(message),
+        }
+    }
+
+    /// Gets the process exit code for the error kind.
+    ///
+    /// Codes start from 3, as 1 conventionally means a generic failure and 2 invalid arguments.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::Config(_) => 3,
+            Self::NotFound(_) => 4,
+            Self::Network(_) => 5,
+            Self::Auth(_) => 6,
+            Self::Quota(_) => 7,
+            Self::Parse(_) => 8,
+            Self::Unsupported(_) => 9,
+        }
+    }
+
+    /// Gets the error message.
+    pub fn message(&self) -> &str {
+        match self {
+            Self::Config(message)
+            | Self::NotFound(message)
+            | Self::Network(message)
+            | Self::Auth(message)
+            | Self::Quota(message)
+            | Self::Parse(message)
+            | Self::Unsupported(message) => message,
+        }
+    }
+
+    /// Describes the context the error occurred in, keeping its kind.
+    pub fn context(self, context: impl fmt::Display) -> Self {
+        let wrap = |message: String| format!("{context} ({message})");
+        match self {
+            Self::Config(message) => Self::Config(wrap(message)),
+            Self::NotFound(message) => Self::NotFound(wrap(message)),
+            Self::Network(message) => Self::Network(wrap(message)),
+            Self::Auth(message) => Self::Auth(wrap(message)),
+            Self::Quota(message) => Self::Quota(wrap(message)),
+            Self::Parse(message) => Self::Parse(wrap(message)),
+            Self::Unsupported(message) => Self::Unsupported(wrap(message)),
+        }
+    }
+
+    /// Checks whether the request may succeed with another provider: the provider could not be
+    /// reached, failed or its quota is exhausted.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Self::Network(_) | Self::Quota(_))
+    }
+}
+
+impl fmt::Display for WeatherError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+impl std::error::Error for WeatherError {}
@@ -0,0 +1,77 @@
+use crate::error::{Result, WeatherError};
+use crate::geocoder::{Geocoder, Location};
+use crate::provider::get_json;
+
+static LOCATION_API_URL: &str = "http://dataservice.accuweather.com/locations/v1";
+
+/// AccuWeather Locations API.
+pub struct AccuWeatherGeocoder {
+    api_key: String,
+}
+
+impl AccuWeatherGeocoder {
+    pub fn new(api_key: String) -> Self {
+        Self { api_key }
+    }
+}
+
+/// Parses location shared by the Locations API endpoints.
+fn parse_location(data: &serde_json::Value) -> Option<Location> {
+    Some(Location {
+        name: data["LocalizedName"].as_str()?.to_string(),
+        country: data["Country"]["ID"].as_str().map(|e| e.to_string()),
+        admin_area: data["AdministrativeArea"]["LocalizedName"]
+            .as_str()
+            .map(|e| e.to_string()),
+        latitude: data["GeoPosition"]["Latitude"].as_f64()?,
+        longitude: data["GeoPosition"]["Longitude"].as_f64()?,
+        timezone: data["TimeZone"]["Name"].as_str().map(|e| e.to_string()),
+        key: Some(data["Key"].as_str()?.to_string()),
+    })
+}
+
+impl Geocoder for AccuWeatherGeocoder {
+    fn search(&self, address: &str) -> Result<Vec<Location>> {
+        let data = get_json(&format!(
+            "{}/cities/search?apikey={}&q={}",
+            LOCATION_API_URL, self.api_key, address
+        ))?;
+
+        Ok(match data.as_array() {
+            Some(array) => array.iter().filter_map(parse_location).collect(),
+            None => Vec::new(),
+        })
+    }
+
+    fn search_postal_code(&self, code: &str, country: Option<&str>) -> Result<Vec<Location>> {
+        let url = match country {
+            Some(country) => format!("{LOCATION_API_URL}/postalcodes/{country}/search"),
+            None => format!("{LOCATION_API_URL}/postalcodes/search"),
+        };
+        let data = get_json(&format!("{}?apikey={}&q={}", url, self.api_key, code))?;
+
+        Ok(match data.as_array() {
+            Some(array) => array.iter().filter_map(parse_location).collect(),
+            None => Vec::new(),
+        })
+    }
+
+    fn reverse(&self, latitude: f64, longitude: f64) -> Result<Option<Location>> {
+        let data = get_json(&format!(
+            "{}/cities/geoposition/search?apikey={}&q={},{}",
+            LOCATION_API_URL, self.api_key, latitude, longitude
+        ))?;
+
+        Ok(parse_location(&data))
+    }
+
+    /// Gets location by AccuWeather location key.
+    fn get_location(&self, id: &str) -> Result<Location> {
+        let data = get_json(&format!(
+            "{}/{}?apikey={}",
+            LOCATION_API_URL, id, self.api_key
+        ))?;
+
+        parse_location(&data).ok_or_else(|| WeatherError::NotFound("Unknown location".to_string()))
+    }
+}
@@ -0,0 +1,54 @@
+use crate::error::{Result, WeatherError};
+use crate::geocoder::{Geocoder, Location};
+
+/// Geocoder trying the geocoders of a provider group in turn.
+///
+/// The next geocoder is tried only if the request may succeed with it,
+/// other errors are returned as is.
+pub struct FallbackGeocoder {
+    geocoders: Vec<Box<dyn Geocoder>>,
+}
+
+impl FallbackGeocoder {
+    /// Creates the geocoder trying the geocoders in the order given.
+    pub fn new(geocoders: Vec<Box<dyn Geocoder>>) -> Self {
+        Self { geocoders }
+    }
+
+    fn try_each<T>(&self, request: impl Fn(&dyn Geocoder) -> Result<T>) -> Result<T> {
+        let mut last_error = None;
+
+        for geocoder in &self.geocoders {
+            match request(geocoder.as_ref()) {
+                Ok(value) => return Ok(value),
+                Err(e) if e.is_transient() => last_error = Some(e),
+                Err(e) => return Err(e),
+            }
+        }
+
+        Err(last_error
+            .unwrap_or_else(|| WeatherError::Config("No geocoders to request".to_string())))
+    }
+}
+
+impl Geocoder for FallbackGeocoder {
+    fn search(&self, address: &str) -> Result<Vec<Location>> {
+        self.try_each(|geocoder| geocoder.search(address))
+    }
+
+    fn search_postal_code(&self, code: &str, country: Option<&str>) -> Result<Vec<Location>> {
+        self.try_each(|geocoder| geocoder.search_postal_code(code, country))
+    }
+
+    fn reverse(&self, latitude: f64, longitude: f64) -> Result<Option<Location>> {
+        self.try_each(|geocoder| geocoder.reverse(latitude, longitude))
+    }
+
+    /// Gets location with the first geocoder only, as identifiers are native to it.
+    fn get_location(&self, id: &str) -> Result<Location> {
+        match self.geocoders.first() {
+            Some(geocoder) => geocoder.get_location(id),
+            None => Err(WeatherError::Config("No geocoders to request".to_string())),
+        }
+    }
+}
@@ -0,0 +1,12 @@
+use crate::arguments;
+use crate::geocoder;
+
+/// Geocoder factory
+pub trait GeocoderFactory {
+    /// Makes geocoder based on specified geocoder config, the provider one is used by default
+    fn make_geocoder(
+        &self,
+        config: &arguments::GeocoderConfig,
+        provider_config: &arguments::WeatherProvider,
+    ) -> Box<dyn geocoder::Geocoder>;
+}
@@ -0,0 +1,306 @@
+use crate::error::{Result, WeatherError};
+use crate::geocoder::{Geocoder, Location};
+
+use kdtree::distance::squared_euclidean;
+use kdtree::KdTree;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Admin area names file looked up next to the cities dump.
+static ADMIN1_CODES_FILE: &str = "admin1CodesASCII.txt";
+
+/// Maximum number of candidates returned.
+const SEARCH_LIMIT: usize = 10;
+
+/// Misspelled names within this edit distance per this many characters still match.
+const FUZZY_CHARACTERS_PER_EDIT: usize = 4;
+
+/// City of the GeoNames dump.
+struct City {
+    id: String,
+    name: String,
+    ascii_name: String,
+    alternate_names: Vec<String>,
+    latitude: f64,
+    longitude: f64,
+    country: String,
+    admin1: String,
+    population: u64,
+    timezone: String,
+}
+
+impl City {
+    fn location(&self, admin_areas: &HashMap<String, String>) -> Location {
+        Location {
+            name: self.name.clone(),
+            country: Some(self.country.clone()).filter(|e| !e.is_empty()),
+            admin_area: admin_areas
+                .get(&format!("{}.{}", self.country, self.admin1))
+                .cloned(),
+            latitude: self.latitude,
+            longitude: self.longitude,
+            timezone: Some(self.timezone.clone()).filter(|e| !e.is_empty()),
+            key: None,
+        }
+    }
+
+    /// Rates how well the name matches the lowercase query, lower is better.
+    fn rate(&self, query: &str) -> Option<Match> {
+        let names = [&self.name, &self.ascii_name]
+            .into_iter()
+            .chain(self.alternate_names.iter());
+
+        names
+            .filter_map(|name| {
+                let name = name.to_lowercase();
+                if name == query {
+                    Some(Match::Exact)
+                } else if name.starts_with(query) {
+                    Some(Match::Prefix)
+                } else {
+                    let max_distance = query.chars().count() / FUZZY_CHARACTERS_PER_EDIT;
+                    let length_difference =
+                        (name.chars().count() as isize - query.chars().count() as isize).abs();
+                    if max_distance == 0 || length_difference as usize > max_distance {
+                        return None;
+                    }
+                    let distance = strsim::levenshtein(&name, query);
+                    (distance <= max_distance).then_some(Match::Fuzzy)
+                }
+            })
+            .min()
+    }
+}
+
+/// Name match quality, better matches go first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Match {
+    Exact,
+    Prefix,
+    Fuzzy,
+}
+
+/// K-d tree of city indices over their points on the unit sphere.
+type CityTree = KdTree<f64, usize, [f64; 3]>;
+
+/// Cities of the GeoNames dump with admin area names.
+struct GeoNames {
+    cities: Vec<City>,
+    admin_areas: HashMap<String, String>,
+
+    /// Built on the first reverse lookup
+    tree: OnceLock<CityTree>,
+}
+
+impl GeoNames {
+    /// Loads GeoNames `cities*.txt` dump and `admin1CodesASCII.txt` next to it, if any.
+    fn load(path: &Path) -> Result<Self> {
+        let file = File::open(path).map_err(|e| {
+            WeatherError::Config(format!(
+                "Failed to open GeoNames dump '{}' ({e})",
+                path.display()
+            ))
+        })?;
+
+        let mut cities = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = line
+                .map_err(|e| WeatherError::Config(format!("Failed to read GeoNames dump ({e})")))?;
+            if let Some(city) = parse_city(&line) {
+                cities.push(city);
+            }
+        }
+
+        if cities.is_empty() {
+            return Err(WeatherError::Config(format!(
+                "No cities found in GeoNames dump '{}'",
+                path.display()
+            )));
+        }
+
+        let admin_areas = match path.parent().map(|e| e.join(ADMIN1_CODES_FILE)) {
+            Some(path) if path.is_file() => load_admin_areas(&path)?,
+            _ => HashMap::new(),
+        };
+
+        Ok(Self {
+            cities,
+            admin_areas,
+            tree: OnceLock::new(),
+        })
+    }
+
+    /// Finds the city nearest to the coordinates using a k-d tree over points on the unit
+    /// sphere, so that distances are not skewed near the poles and the antimeridian.
+    fn nearest(&self, latitude: f64, longitude: f64) -> Option<&City> {
+        let tree = self.tree.get_or_init(|| {
+            let mut tree = KdTree::with_capacity(3, self.cities.len());
+            for (index, city) in self.cities.iter().enumerate() {
+                // Cities with non-finite coordinates cannot be placed and are skipped.
+                let _ = tree.add(unit_vector(city.latitude, city.longitude), index);
+            }
+            tree
+        });
+
+        let nearest = tree
+            .nearest(&unit_vector(latitude, longitude), 1, &squared_euclidean)
+            .ok()?;
+        nearest.first().map(|e| &self.cities[*e.1])
+    }
+}
+
+/// Converts coordinates in degrees into a point on the unit sphere.
+fn unit_vector(latitude: f64, longitude: f64) -> [f64; 3] {
+    let (latitude, longitude) = (latitude.to_radians(), longitude.to_radians());
+    [
+        latitude.cos() * longitude.cos(),
+        latitude.cos() * longitude.sin(),
+        latitude.sin(),
+    ]
+}
+
+/// Parses tab-separated line of the GeoNames geoname table.
+fn parse_city(line: &str) -> Option<City> {
+    let columns: Vec<&str> = line.split('\t').collect();
+    if columns.len() < 18 {
+        return None;
+    }
+
+    Some(City {
+        id: columns[0].to_string(),
+        name: columns[1].to_string(),
+        ascii_name: columns[2].to_string(),
+        alternate_names: columns[3]
+            .split(',')
+            .filter(|e| !e.is_empty())
+            .map(|e| e.to_string())
+            .collect(),
+        latitude: columns[4].parse().ok()?,
+        longitude: columns[5].parse().ok()?,
+        country: columns[8].to_string(),
+        admin1: columns[10].to_string(),
+        population: columns[14].parse().unwrap_or(0),
+        timezone: columns[17].to_string(),
+    })
+}
+
+/// Loads admin area names keyed by `<country>.<admin1>` codes.
+fn load_admin_areas(path: &Path) -> Result<HashMap<String, String>> {
+    let file = File::open(path).map_err(|e| {
+        WeatherError::Config(format!(
+            "Failed to open GeoNames admin codes '{}' ({e})",
+            path.display()
+        ))
+    })?;
+
+    let mut admin_areas = HashMap::new();
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(|e| {
+            WeatherError::Config(format!("Failed to read GeoNames admin codes ({e})"))
+        })?;
+        let mut columns = line.split('\t');
+        if let (Some(code), Some(name)) = (columns.next(), columns.next()) {
+            admin_areas.insert(code.to_string(), name.to_string());
+        }
+    }
+
+    Ok(admin_areas)
+}
+
+/// Offline geocoder backed by GeoNames cities dump.
+///
+/// Matches names, ASCII names and alternate names, exact matches first, then prefixes,
+/// then misspellings, more populated cities first within the same match quality.
+/// The dump is loaded on the first request and kept for the following ones.
+pub struct GeoNamesGeocoder {
+    path: PathBuf,
+    geonames: OnceLock<GeoNames>,
+}
+
+impl GeoNamesGeocoder {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            geonames: OnceLock::new(),
+        }
+    }
+
+    /// Gets the dump, loading it on the first call.
+    fn geonames(&self) -> Result<&GeoNames> {
+        if let Some(geonames) = self.geonames.get() {
+            return Ok(geonames);
+        }
+
+        let geonames = GeoNames::load(&self.path)?;
+        Ok(self.geonames.get_or_init(|| geonames))
+    }
+}
+
+impl Geocoder for GeoNamesGeocoder {
+    /// Searches by name, the trailing comma-separated parts narrow down by country code
+    /// or admin area, e.g. `Springfield, IL, US`.
+    fn search(&self, address: &str) -> Result<Vec<Location>> {
+        let geonames = self.geonames()?;
+
+        let mut parts = address.split(',').map(|e| e.trim().to_lowercase());
+        let query = match parts.next() {
+            Some(query) if !query.is_empty() => query,
+            _ => return Err(WeatherError::Parse("Empty address".to_string())),
+        };
+        let qualifiers: Vec<String> = parts.filter(|e| !e.is_empty()).collect();
+
+        let mut matches: Vec<(Match, &City, Location)> = geonames
+            .cities
+            .iter()
+            .filter_map(|city| {
+                let rate = city.rate(&query)?;
+                let location = city.location(&geonames.admin_areas);
+                let qualified = qualifiers.iter().all(|qualifier| {
+                    city.country.eq_ignore_ascii_case(qualifier)
+                        || city.admin1.eq_ignore_ascii_case(qualifier)
+                        || location
+                            .admin_area
+                            .as_ref()
+                            .is_some_and(|e| e.to_lowercase().starts_with(qualifier.as_str()))
+                });
+                qualified.then_some((rate, city, location))
+            })
+            .collect();
+
+        // Only the best match quality is returned, more populated cities first.
+        let best = match matches.iter().map(|e| e.0).min() {
+            Some(best) => best,
+            None => return Ok(Vec::new()),
+        };
+        matches.retain(|e| e.0 == best);
+        matches.sort_by_key(|e| std::cmp::Reverse(e.1.population));
+
+        Ok(matches
+            .into_iter()
+            .take(SEARCH_LIMIT)
+            .map(|e| e.2)
+            .collect())
+    }
+
+    /// Finds the nearest city of the dump.
+    fn reverse(&self, latitude: f64, longitude: f64) -> Result<Option<Location>> {
+        let geonames = self.geonames()?;
+
+        Ok(geonames
+            .nearest(latitude, longitude)
+            .map(|city| city.location(&geonames.admin_areas)))
+    }
+
+    /// Gets location by GeoNames ID.
+    fn get_location(&self, id: &str) -> Result<Location> {
+        let geonames = self.geonames()?;
+
+        match geonames.cities.iter().find(|e| e.id == id) {
+            Some(city) => Ok(city.location(&geonames.admin_areas)),
+            None => Err(WeatherError::NotFound("Unknown location".to_string())),
+        }
+    }
+}
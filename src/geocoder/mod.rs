@@ -0,0 +1,131 @@
+mod fallback_geocoder;
+mod geocoder_factory;
+mod production_geocoder_factory;
+
+mod accu_weather;
+mod geonames;
+mod open_meteo;
+mod open_weather;
+mod weather_api;
+
+use crate::error::{Result, WeatherError};
+use crate::provider::units::Length;
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+pub use accu_weather::AccuWeatherGeocoder;
+pub use fallback_geocoder::FallbackGeocoder;
+pub use geocoder_factory::GeocoderFactory;
+pub use geonames::GeoNamesGeocoder;
+pub use open_meteo::OpenMeteoGeocoder;
+pub use open_weather::OpenWeatherGeocoder;
+pub use production_geocoder_factory::ProductionGeocoderFactory;
+pub use weather_api::WeatherApiGeocoder;
+
+/// Resolved location.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Location {
+    /// Place name
+    pub name: String,
+
+    /// Country code (ISO 3166-1 alpha-2) or name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+
+    /// First-level administrative area (state, region, oblast)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub admin_area: Option<String>,
+
+    /// Latitude in degrees
+    pub latitude: f64,
+
+    /// Longitude in degrees
+    pub longitude: f64,
+
+    /// IANA time zone name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+
+    /// AccuWeather location key, looked up by coordinates if not provided
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+}
+
+/// Mean Earth radius.
+const EARTH_RADIUS_METERS: f64 = 6_371_008.8;
+
+impl Location {
+    /// Great-circle distance to the coordinates.
+    pub fn distance(&self, latitude: f64, longitude: f64) -> Length {
+        let (lat1, lat2) = (self.latitude.to_radians(), latitude.to_radians());
+        let delta_lat = lat2 - lat1;
+        let delta_lon = (longitude - self.longitude).to_radians();
+
+        let a = (delta_lat / 2.0).sin().powi(2)
+            + lat1.cos() * lat2.cos() * (delta_lon / 2.0).sin().powi(2);
+        Length::from_meters(2.0 * EARTH_RADIUS_METERS * a.sqrt().min(1.0).asin())
+    }
+
+    /// Checks whether the location is within the country (code or name)
+    /// and the region (administrative area name prefix), case-insensitive.
+    pub fn matches(&self, country: Option<&str>, region: Option<&str>) -> bool {
+        let country_matches = match (country, &self.country) {
+            (Some(country), Some(own)) => own.eq_ignore_ascii_case(country.trim()),
+            (Some(_), None) => false,
+            (None, _) => true,
+        };
+        let region_matches = match (region, &self.admin_area) {
+            (Some(region), Some(own)) => own
+                .to_lowercase()
+                .starts_with(&region.trim().to_lowercase()),
+            (Some(_), None) => false,
+            (None, _) => true,
+        };
+        country_matches && region_matches
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if let Some(admin_area) = &self.admin_area {
+            if *admin_area != self.name {
+                write!(f, ", {admin_area}")?;
+            }
+        }
+        if let Some(country) = &self.country {
+            write!(f, ", {country}")?;
+        }
+        write!(f, " ({:.4}, {:.4})", self.latitude, self.longitude)
+    }
+}
+
+/// Location resolver.
+pub trait Geocoder {
+    /// Searches locations matching the address.
+    ///
+    /// Returns all candidates found, the best matches first.
+    fn search(&self, address: &str) -> Result<Vec<Location>>;
+
+    /// Searches locations by postal code, optionally within the country (ISO 3166 code).
+    fn search_postal_code(&self, _code: &str, _country: Option<&str>) -> Result<Vec<Location>> {
+        Err(WeatherError::Unsupported(
+            "Postal code search is not supported by this geocoder".to_string(),
+        ))
+    }
+
+    /// Finds the nearest named place to the coordinates, if any.
+    fn reverse(&self, _latitude: f64, _longitude: f64) -> Result<Option<Location>> {
+        Err(WeatherError::Unsupported(
+            "Reverse geocoding is not supported by this geocoder".to_string(),
+        ))
+    }
+
+    /// Gets location by the identifier native to the geocoder.
+    fn get_location(&self, _id: &str) -> Result<Location> {
+        Err(WeatherError::Unsupported(
+            "Location identifiers are not supported by this geocoder".to_string(),
+        ))
+    }
+}
@@ -0,0 +1,63 @@
+use crate::error::{Result, WeatherError};
+use crate::geocoder::{Geocoder, Location};
+use crate::provider::get_json;
+
+static SEARCH_API_URL: &str = "https://geocoding-api.open-meteo.com/v1/search";
+static GET_API_URL: &str = "https://geocoding-api.open-meteo.com/v1/get";
+
+/// Maximum number of candidates returned.
+const SEARCH_LIMIT: u32 = 5;
+
+/// Open-Meteo Geocoding API, based on GeoNames and free to use without a key.
+pub struct OpenMeteoGeocoder;
+
+fn parse_location(data: &serde_json::Value) -> Option<Location> {
+    Some(Location {
+        name: data["name"].as_str()?.to_string(),
+        country: data["country_code"].as_str().map(|e| e.to_string()),
+        admin_area: data["admin1"].as_str().map(|e| e.to_string()),
+        latitude: data["latitude"].as_f64()?,
+        longitude: data["longitude"].as_f64()?,
+        timezone: data["timezone"].as_str().map(|e| e.to_string()),
+        key: None,
+    })
+}
+
+/// Parses search results, which are absent if nothing is found.
+fn parse_results(data: &serde_json::Value) -> Vec<Location> {
+    match data["results"].as_array() {
+        Some(array) => array.iter().filter_map(parse_location).collect(),
+        None => Vec::new(),
+    }
+}
+
+impl Geocoder for OpenMeteoGeocoder {
+    fn search(&self, address: &str) -> Result<Vec<Location>> {
+        let data = get_json(&format!(
+            "{}?name={}&count={}&format=json",
+            SEARCH_API_URL, address, SEARCH_LIMIT,
+        ))?;
+
+        Ok(parse_results(&data))
+    }
+
+    /// Postal codes are searched the same way as names, matching any country unless specified.
+    fn search_postal_code(&self, code: &str, country: Option<&str>) -> Result<Vec<Location>> {
+        let mut url = format!(
+            "{}?name={}&count={}&format=json",
+            SEARCH_API_URL, code, SEARCH_LIMIT,
+        );
+        if let Some(country) = country {
+            url.push_str(&format!("&countryCode={country}"));
+        }
+
+        Ok(parse_results(&get_json(&url)?))
+    }
+
+    /// Resolves GeoNames ID.
+    fn get_location(&self, id: &str) -> Result<Location> {
+        let data = get_json(&format!("{}?id={}", GET_API_URL, id))?;
+
+        parse_location(&data).ok_or_else(|| WeatherError::NotFound("Unknown location".to_string()))
+    }
+}
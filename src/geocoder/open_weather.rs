@@ -0,0 +1,99 @@
+use crate::error::{Result, WeatherError};
+use crate::geocoder::{Geocoder, Location};
+use crate::provider::get_json;
+
+static GEOCODING_API_URL: &str = "https://api.openweathermap.org/geo/1.0/direct";
+static REVERSE_GEOCODING_API_URL: &str = "https://api.openweathermap.org/geo/1.0/reverse";
+static ZIP_GEOCODING_API_URL: &str = "https://api.openweathermap.org/geo/1.0/zip";
+static WEATHER_API_URL: &str = "https://api.openweathermap.org/data/2.5/weather";
+
+/// Maximum number of candidates returned, the API allows up to 5.
+const SEARCH_LIMIT: u32 = 5;
+
+/// OpenWeather Geocoding API.
+pub struct OpenWeatherGeocoder {
+    api_key: String,
+}
+
+impl OpenWeatherGeocoder {
+    pub fn new(api_key: String) -> Self {
+        Self { api_key }
+    }
+}
+
+/// Parses direct geocoding entry, which has no time zone.
+fn parse_location(data: &serde_json::Value) -> Option<Location> {
+    Some(Location {
+        name: data["name"].as_str()?.to_string(),
+        country: data["country"].as_str().map(|e| e.to_string()),
+        admin_area: data["state"].as_str().map(|e| e.to_string()),
+        latitude: data["lat"].as_f64()?,
+        longitude: data["lon"].as_f64()?,
+        timezone: None,
+        key: None,
+    })
+}
+
+/// Parses location of the current weather response.
+fn parse_weather_location(data: &serde_json::Value) -> Option<Location> {
+    Some(Location {
+        name: data["name"].as_str()?.to_string(),
+        country: data["sys"]["country"].as_str().map(|e| e.to_string()),
+        admin_area: None,
+        latitude: data["coord"]["lat"].as_f64()?,
+        longitude: data["coord"]["lon"].as_f64()?,
+        timezone: None,
+        key: None,
+    })
+}
+
+impl Geocoder for OpenWeatherGeocoder {
+    fn search(&self, address: &str) -> Result<Vec<Location>> {
+        let data = get_json(&format!(
+            "{}?q={}&limit={}&appid={}",
+            GEOCODING_API_URL, address, SEARCH_LIMIT, self.api_key,
+        ))?;
+
+        Ok(match data.as_array() {
+            Some(array) => array.iter().filter_map(parse_location).collect(),
+            None => Vec::new(),
+        })
+    }
+
+    /// Postal codes are searched within US unless the country is specified.
+    fn search_postal_code(&self, code: &str, country: Option<&str>) -> Result<Vec<Location>> {
+        let zip = match country {
+            Some(country) => format!("{code},{country}"),
+            None => code.to_string(),
+        };
+        let data = get_json(&format!(
+            "{}?zip={}&appid={}",
+            ZIP_GEOCODING_API_URL, zip, self.api_key,
+        ))?;
+
+        Ok(parse_location(&data).into_iter().collect())
+    }
+
+    fn reverse(&self, latitude: f64, longitude: f64) -> Result<Option<Location>> {
+        let data = get_json(&format!(
+            "{}?lat={}&lon={}&limit=1&appid={}",
+            REVERSE_GEOCODING_API_URL, latitude, longitude, self.api_key,
+        ))?;
+
+        Ok(data
+            .as_array()
+            .and_then(|array| array.first())
+            .and_then(parse_location))
+    }
+
+    /// Resolves OpenWeather city ID, which is only accepted by the weather API.
+    fn get_location(&self, id: &str) -> Result<Location> {
+        let data = get_json(&format!(
+            "{}?id={}&appid={}",
+            WEATHER_API_URL, id, self.api_key,
+        ))?;
+
+        parse_weather_location(&data)
+            .ok_or_else(|| WeatherError::NotFound("Unknown location".to_string()))
+    }
+}
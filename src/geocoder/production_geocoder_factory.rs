@@ -0,0 +1,33 @@
+use crate::arguments;
+use crate::geocoder::{
+    geocoder_factory, AccuWeatherGeocoder, GeoNamesGeocoder, Geocoder, OpenMeteoGeocoder,
+    OpenWeatherGeocoder, WeatherApiGeocoder,
+};
+
+/// Geocoder factory to be used in production.
+pub struct ProductionGeocoderFactory;
+
+impl geocoder_factory::GeocoderFactory for ProductionGeocoderFactory {
+    /// Makes geocoder according to specified config
+    fn make_geocoder(
+        &self,
+        config: &arguments::GeocoderConfig,
+        provider_config: &arguments::WeatherProvider,
+    ) -> Box<dyn Geocoder> {
+        if let arguments::GeocoderConfig::GeoNames { path } = config {
+            return Box::new(GeoNamesGeocoder::new(path.clone()));
+        }
+
+        // Keyless providers are never stored with an empty key, so the key is there if required.
+        let api_key = provider_config.api_key.clone().unwrap_or_default();
+        match provider_config.provider {
+            arguments::Provider::OpenWeather => Box::new(OpenWeatherGeocoder::new(api_key)),
+            arguments::Provider::AccuWeather => Box::new(AccuWeatherGeocoder::new(api_key)),
+            arguments::Provider::WeatherApi => Box::new(WeatherApiGeocoder::new(api_key)),
+            // NWS and MET Norway have no geocoding API, the keyless Open-Meteo one is used instead.
+            arguments::Provider::OpenMeteo
+            | arguments::Provider::Nws
+            | arguments::Provider::MetNorway => Box::new(OpenMeteoGeocoder),
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use crate::error::{Result, WeatherError};
+use crate::geocoder::{Geocoder, Location};
+use crate::provider::get_json;
+
+static SEARCH_API_URL: &str = "https://api.weatherapi.com/v1/search.json";
+
+/// WeatherAPI.com Search API.
+pub struct WeatherApiGeocoder {
+    api_key: String,
+}
+
+impl WeatherApiGeocoder {
+    pub fn new(api_key: String) -> Self {
+        Self { api_key }
+    }
+
+    /// Searches locations by the query accepted by any WeatherAPI.com endpoint.
+    fn query(&self, query: &str) -> Result<Vec<Location>> {
+        let data = get_json(&format!(
+            "{}?key={}&q={}",
+            SEARCH_API_URL, self.api_key, query
+        ))?;
+
+        Ok(match data.as_array() {
+            Some(array) => array.iter().filter_map(parse_location).collect(),
+            None => Vec::new(),
+        })
+    }
+}
+
+/// Parses search entry, which has the country name and no time zone.
+fn parse_location(data: &serde_json::Value) -> Option<Location> {
+    Some(Location {
+        name: data["name"].as_str()?.to_string(),
+        country: data["country"].as_str().map(|e| e.to_string()),
+        admin_area: data["region"]
+            .as_str()
+            .filter(|e| !e.is_empty())
+            .map(|e| e.to_string()),
+        latitude: data["lat"].as_f64()?,
+        longitude: data["lon"].as_f64()?,
+        timezone: None,
+        key: None,
+    })
+}
+
+impl Geocoder for WeatherApiGeocoder {
+    fn search(&self, address: &str) -> Result<Vec<Location>> {
+        self.query(address)
+    }
+
+    /// Nearest place is looked up by the coordinates query.
+    fn reverse(&self, latitude: f64, longitude: f64) -> Result<Option<Location>> {
+        Ok(self
+            .query(&format!("{latitude:.4},{longitude:.4}"))?
+            .into_iter()
+            .next())
+    }
+
+    /// Resolves WeatherAPI.com location ID.
+    fn get_location(&self, id: &str) -> Result<Location> {
+        self.query(&format!("id:{id}"))?
+            .into_iter()
+            .next()
+            .ok_or_else(|| WeatherError::NotFound("Unknown location".to_string()))
+    }
+}
@@ -1,3 +1,7 @@
 pub mod arguments;
+pub mod cache;
+pub mod error;
+pub mod geocoder;
+pub mod output;
 pub mod processor;
 pub mod provider;
@@ -3,6 +3,11 @@ use weather::processor;
 
 use clap::Parser;
 
+use std::process;
+
+/// Exit code of successful runs showing expired stored data instead of fresh data.
+const STALE_DATA_EXIT_CODE: i32 = 10;
+
 fn main() {
     let cli = arguments::Cli::parse();
 
@@ -10,6 +15,9 @@ fn main() {
     let processor = processor::Processor::new(dependency_factory);
 
     if let Err(e) = processor.run(cli) {
-        println!("{e}");
+        eprintln!("Error: {e}");
+        process::exit(e.exit_code());
+    } else if processor.served_stale() {
+        process::exit(STALE_DATA_EXIT_CODE);
     }
 }
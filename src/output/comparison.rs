@@ -0,0 +1,161 @@
+use crate::error::Result;
+use crate::provider::units::Units;
+use crate::provider::{FieldValue, WeatherInfo};
+
+/// Aggregate of the values different providers reported for the same field.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Summary {
+    pub median: f64,
+    pub mean: f64,
+
+    /// Difference between the largest and the smallest value
+    pub spread: f64,
+}
+
+impl Summary {
+    /// Summarizes the values, at least two are needed to compare.
+    pub fn of(values: &[f64]) -> Option<Self> {
+        if values.len() < 2 {
+            return None;
+        }
+
+        let mut sorted = values.to_vec();
+        sorted.sort_by(f64::total_cmp);
+
+        let middle = sorted.len() / 2;
+        let median = match sorted.len() % 2 {
+            0 => (sorted[middle - 1] + sorted[middle]) / 2.0,
+            _ => sorted[middle],
+        };
+
+        Some(Self {
+            median,
+            mean: sorted.iter().sum::<f64>() / sorted.len() as f64,
+            spread: sorted[sorted.len() - 1] - sorted[0],
+        })
+    }
+}
+
+/// Fields not summarized: wind direction is circular, so its mean and spread are misleading.
+const UNSUMMARIZED_FIELDS: [&str; 1] = ["wind_direction"];
+
+/// Renders table of the fields reported by the providers side by side,
+/// with numeric fields summarized across them.
+///
+/// Providers failed are listed with their errors below the table.
+pub fn render_comparison(results: &[(String, Result<WeatherInfo>)], units: Units) -> String {
+    let answered: Vec<(&String, Vec<_>)> = results
+        .iter()
+        .filter_map(|(name, result)| match result {
+            Ok(weather) => Some((name, weather.display(units).fields())),
+            Err(_) => None,
+        })
+        .collect();
+
+    let mut header = vec!["field".to_string()];
+    header.extend(answered.iter().map(|(name, _)| name.to_string()));
+    header.extend(["median", "mean", "spread"].map(String::from));
+    let mut rows = vec![header];
+
+    if let Some((_, first)) = answered.first() {
+        for (index, field) in first.iter().enumerate() {
+            let values: Vec<&Option<FieldValue>> = answered
+                .iter()
+                .map(|(_, fields)| &fields[index].value)
+                .collect();
+            // Fields no provider reported are not shown.
+            if values.iter().all(|e| e.is_none()) {
+                continue;
+            }
+
+            let mut row = vec![match field.unit {
+                Some(unit) => format!("{} ({unit})", field.name),
+                None => field.name.to_string(),
+            }];
+            row.extend(values.iter().map(|value| match value {
+                Some(value) => value.to_string(),
+                None => String::new(),
+            }));
+
+            let numbers: Vec<f64> = values
+                .iter()
+                .filter_map(|value| match value {
+                    Some(FieldValue::Number(value)) => Some(*value),
+                    Some(FieldValue::Integer(value)) => Some(*value as f64),
+                    _ => None,
+                })
+                .collect();
+            match Summary::of(&numbers) {
+                Some(summary) if !UNSUMMARIZED_FIELDS.contains(&field.name) => {
+                    // Aggregates get a decimal place more than the values they are made of.
+                    let places = numbers
+                        .iter()
+                        .map(|e| decimal_places(*e))
+                        .max()
+                        .unwrap_or(0)
+                        + 1;
+                    row.extend(
+                        [summary.median, summary.mean, summary.spread]
+                            .map(|e| round(e, places).to_string()),
+                    );
+                }
+                _ => row.extend([String::new(), String::new(), String::new()]),
+            }
+
+            rows.push(row);
+        }
+    }
+
+    let mut output = String::new();
+    // Header is not worth showing if no provider answered.
+    if !answered.is_empty() {
+        output.push_str(&render_rows(&rows));
+    }
+
+    for (name, result) in results {
+        if let Err(e) = result {
+            output.push_str(&format!("{name}: {e}\n"));
+        }
+    }
+
+    output
+}
+
+/// Renders rows aligned in columns.
+fn render_rows(rows: &[Vec<String>]) -> String {
+    let widths: Vec<usize> = (0..rows[0].len())
+        .map(|column| {
+            rows.iter()
+                .map(|row| row[column].chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let mut output = String::new();
+    for row in rows {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:width$}", cell, width = width))
+            .collect();
+        output.push_str(cells.join("  ").trim_end());
+        output.push('\n');
+    }
+    output
+}
+
+/// Counts decimal places of the value as it is displayed.
+fn decimal_places(value: f64) -> usize {
+    let value = value.to_string();
+    match value.find('.') {
+        Some(point) => value.len() - point - 1,
+        None => 0,
+    }
+}
+
+fn round(value: f64, places: usize) -> f64 {
+    let factor = 10_f64.powi(places as i32);
+    // Negative zero is displayed with the sign otherwise.
+    (value * factor).round() / factor + 0.0
+}
@@ -0,0 +1,362 @@
+mod comparison;
+mod template;
+
+pub use comparison::{render_comparison, Summary};
+pub use template::Template;
+
+use crate::error::{Result, WeatherError};
+use crate::geocoder::Location;
+use crate::provider::units::{Length, Quantity, Unit, Units};
+use crate::provider::{Forecast, WeatherInfo, WeatherInfoDisplay};
+
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use clap::ValueEnum;
+use serde::ser::{SerializeMap, Serializer};
+use serde::Serialize;
+
+/// Version of the machine-readable output schema.
+///
+/// Incremented on incompatible changes only, new fields may be added within the version.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Output formats.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable text
+    #[default]
+    Text,
+
+    /// JSON document with all entries
+    Json,
+
+    /// YAML document with all entries
+    Yaml,
+
+    /// CSV with a header row, an entry per row
+    Csv,
+
+    /// JSON object per line, an entry per line
+    Ndjson,
+
+    /// Aligned table of the provided fields
+    Table,
+}
+
+/// Output settings.
+pub enum Output {
+    /// Predefined format
+    Format(OutputFormat),
+
+    /// Template rendered per entry, missing values are replaced with the placeholder
+    Template {
+        template: Template,
+        placeholder: String,
+    },
+}
+
+/// Renders weather conditions the specified time refers to.
+pub fn render_weather(
+    weather: &WeatherInfo,
+    time: DateTime<Utc>,
+    units: Units,
+    output: &Output,
+) -> Result<String> {
+    match output {
+        Output::Format(OutputFormat::Text) => Ok(format!("{}\n", weather.display(units))),
+        Output::Format(format) => render_entries(&[(time, weather)], units, *format),
+        Output::Template {
+            template,
+            placeholder,
+        } => Ok(render_template(
+            &[(time, weather)],
+            units,
+            template,
+            placeholder,
+        )),
+    }
+}
+
+/// Renders the header naming the place nearest to the queried coordinates.
+///
+/// Only human-readable text gets the header, other formats are left intact.
+pub fn render_nearest_place(
+    place: &Location,
+    distance: Length,
+    units: Units,
+    output: &Output,
+) -> String {
+    match output {
+        Output::Format(OutputFormat::Text) => format!(
+            "Nearest place: {} ({} away)\n",
+            place,
+            distance.format(units.distance)
+        ),
+        _ => String::new(),
+    }
+}
+
+/// Renders the notice that the data shown was stored earlier and has expired.
+pub fn render_stale_notice(stored_at: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    format!(
+        "Stored data from {} ({} old)",
+        stored_at.format("%Y-%m-%d %H:%M UTC"),
+        format_age(now - stored_at)
+    )
+}
+
+/// Formats the age in the largest whole units.
+fn format_age(age: Duration) -> String {
+    let (value, unit) = if age.num_days() > 0 {
+        (age.num_days(), "day")
+    } else if age.num_hours() > 0 {
+        (age.num_hours(), "hour")
+    } else {
+        (age.num_minutes().max(0), "minute")
+    };
+
+    match value {
+        1 => format!("{value} {unit}"),
+        _ => format!("{value} {unit}s"),
+    }
+}
+
+/// Renders weather forecast.
+pub fn render_forecast(forecast: &Forecast, units: Units, output: &Output) -> Result<String> {
+    let entries: Vec<(DateTime<Utc>, &WeatherInfo)> = forecast
+        .entries
+        .iter()
+        .map(|e| (e.time, &e.weather))
+        .collect();
+
+    match output {
+        Output::Format(OutputFormat::Text) => Ok(forecast.display(units).to_string()),
+        Output::Format(format) => render_entries(&entries, units, *format),
+        Output::Template {
+            template,
+            placeholder,
+        } => Ok(render_template(&entries, units, template, placeholder)),
+    }
+}
+
+/// Renders the template per entry, each on its own line.
+fn render_template(
+    entries: &[(DateTime<Utc>, &WeatherInfo)],
+    units: Units,
+    template: &Template,
+    placeholder: &str,
+) -> String {
+    let mut output = String::new();
+    for (time, weather) in entries {
+        output.push_str(&template.render(*time, &weather.display(units), placeholder));
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+    }
+    output
+}
+
+fn render_entries(
+    entries: &[(DateTime<Utc>, &WeatherInfo)],
+    units: Units,
+    format: OutputFormat,
+) -> Result<String> {
+    let records = entries.iter().map(|(time, weather)| Record {
+        header: None,
+        time: *time,
+        weather: weather.display(units),
+    });
+
+    match format {
+        OutputFormat::Json => {
+            let document = Document {
+                version: SCHEMA_VERSION,
+                units: UnitSymbols::from(units),
+                entries: records.collect(),
+            };
+            serde_json::to_string_pretty(&document)
+                .map(|e| e + "\n")
+                .map_err(|e| WeatherError::Parse(format!("Failed to serialize output ({e})")))
+        }
+        OutputFormat::Yaml => {
+            let document = Document {
+                version: SCHEMA_VERSION,
+                units: UnitSymbols::from(units),
+                entries: records.collect(),
+            };
+            serde_yaml::to_string(&document)
+                .map_err(|e| WeatherError::Parse(format!("Failed to serialize output ({e})")))
+        }
+        OutputFormat::Ndjson => {
+            let mut output = String::new();
+            for mut record in records {
+                record.header = Some((SCHEMA_VERSION, UnitSymbols::from(units)));
+                let line = serde_json::to_string(&record).map_err(|e| {
+                    WeatherError::Parse(format!("Failed to serialize output ({e})"))
+                })?;
+                output.push_str(&line);
+                output.push('\n');
+            }
+            Ok(output)
+        }
+        OutputFormat::Csv => Ok(render_csv(records.collect())),
+        OutputFormat::Table => Ok(render_table(records.collect())),
+        OutputFormat::Text => Ok(entries
+            .iter()
+            .map(|(_, weather)| format!("{}\n", weather.display(units)))
+            .collect::<Vec<String>>()
+            .join("\n")),
+    }
+}
+
+/// Renders CSV with all fields, missing values are left empty.
+fn render_csv(records: Vec<Record>) -> String {
+    let mut output = String::from("version,time");
+    for name in WeatherInfoDisplay::FIELD_NAMES {
+        output.push(',');
+        output.push_str(name);
+    }
+    output.push('\n');
+
+    for record in records {
+        output.push_str(&format!("{},{}", SCHEMA_VERSION, format_time(record.time)));
+        for field in record.weather.fields() {
+            output.push(',');
+            if let Some(value) = field.value {
+                output.push_str(&escape_csv(&value.to_string()));
+            }
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+fn escape_csv(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Renders table with the fields provided by at least one entry.
+fn render_table(records: Vec<Record>) -> String {
+    let rows: Vec<Vec<String>> = records
+        .iter()
+        .map(|record| {
+            let mut row = vec![format_time(record.time)];
+            row.extend(
+                record
+                    .weather
+                    .fields()
+                    .into_iter()
+                    .map(|field| field.value.map(|e| e.to_string()).unwrap_or_default()),
+            );
+            row
+        })
+        .collect();
+
+    let mut header = vec!["time".to_string()];
+    if let Some(record) = records.first() {
+        header.extend(
+            record
+                .weather
+                .fields()
+                .into_iter()
+                .map(|field| match field.unit {
+                    Some(unit) => format!("{} ({unit})", field.name),
+                    None => field.name.to_string(),
+                }),
+        );
+    }
+
+    // Time column is always shown.
+    let columns: Vec<usize> = (0..header.len())
+        .filter(|column| *column == 0 || rows.iter().any(|row| !row[*column].is_empty()))
+        .collect();
+
+    let widths: Vec<usize> = columns
+        .iter()
+        .map(|column| {
+            rows.iter()
+                .map(|row| row[*column].chars().count())
+                .chain([header[*column].chars().count()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let format_row = |row: &Vec<String>| {
+        let cells: Vec<String> = columns
+            .iter()
+            .zip(&widths)
+            .map(|(column, width)| format!("{:width$}", row[*column], width = width))
+            .collect();
+        cells.join("  ").trim_end().to_string() + "\n"
+    };
+
+    let mut output = format_row(&header);
+    for row in &rows {
+        output.push_str(&format_row(row));
+    }
+    output
+}
+
+fn format_time(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// Machine-readable document.
+#[derive(Serialize)]
+struct Document<'a> {
+    version: u32,
+    units: UnitSymbols,
+    entries: Vec<Record<'a>>,
+}
+
+/// Weather entry serialized as a flat map of time and weather fields.
+struct Record<'a> {
+    /// Schema version and units for the self-contained records
+    header: Option<(u32, UnitSymbols)>,
+
+    time: DateTime<Utc>,
+    weather: WeatherInfoDisplay<'a>,
+}
+
+impl Serialize for Record<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let fields = self.weather.fields();
+        let mut map = serializer.serialize_map(None)?;
+        if let Some((version, units)) = &self.header {
+            map.serialize_entry("version", version)?;
+            map.serialize_entry("units", units)?;
+        }
+        map.serialize_entry("time", &format_time(self.time))?;
+        for field in fields {
+            map.serialize_entry(field.name, &field.value)?;
+        }
+        map.end()
+    }
+}
+
+/// Symbols of the units values are serialized in.
+#[derive(Serialize)]
+struct UnitSymbols {
+    temperature: &'static str,
+    pressure: &'static str,
+    speed: &'static str,
+    precipitation: &'static str,
+    distance: &'static str,
+}
+
+impl From<Units> for UnitSymbols {
+    fn from(units: Units) -> Self {
+        Self {
+            temperature: units.temperature.symbol(),
+            pressure: units.pressure.symbol(),
+            speed: units.speed.symbol(),
+            precipitation: units.precipitation.symbol(),
+            distance: units.distance.symbol(),
+        }
+    }
+}
@@ -0,0 +1,208 @@
+use crate::error::{Result, WeatherError};
+use crate::provider::{FieldValue, WeatherInfoDisplay};
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, SecondsFormat, Utc};
+
+/// Suffix of the pseudo-fields resolving to the field unit symbol.
+static UNIT_SUFFIX: &str = "_unit";
+
+/// Output template.
+///
+/// Text with `{field}` or `{field:spec}` placeholders rendered against the weather fields
+/// and `time`. `{field_unit}` is replaced with the unit symbol of the field.
+/// The spec is `[align][width][.precision]` with `<`, `>` or `^` alignment, or `strftime`
+/// format for times (e.g. `{sunrise:%H:%M}`). Use `{{` and `}}` for literal braces.
+#[derive(Debug)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+#[derive(Debug)]
+enum Part {
+    Text(String),
+    Field { name: String, spec: Spec },
+}
+
+#[derive(Debug, Default)]
+struct Spec {
+    align: Option<char>,
+    width: Option<usize>,
+    precision: Option<usize>,
+    time_format: Option<String>,
+}
+
+impl Template {
+    /// Parses template, failing on unknown fields or malformed placeholders.
+    pub fn parse(template: &str) -> Result<Self> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut placeholder = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => placeholder.push(c),
+                            None => {
+                                return Err(WeatherError::Parse(format!(
+                                    "Unclosed placeholder '{{{placeholder}' in template"
+                                )))
+                            }
+                        }
+                    }
+
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(parse_placeholder(&placeholder)?);
+                }
+                '}' => return Err(WeatherError::Parse("Unmatched '}' in template".to_string())),
+                c => text.push(c),
+            }
+        }
+
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+
+        Ok(Self { parts })
+    }
+
+    /// Renders the template, missing values are replaced with the placeholder.
+    pub fn render(
+        &self,
+        time: DateTime<Utc>,
+        weather: &WeatherInfoDisplay,
+        placeholder: &str,
+    ) -> String {
+        let fields = weather.fields();
+        let mut output = String::new();
+
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => output.push_str(text),
+                Part::Field { name, spec } => {
+                    let value = if name == "time" {
+                        Some(format_value(&FieldValue::Time(time), spec))
+                    } else if let Some(name) = name.strip_suffix(UNIT_SUFFIX) {
+                        fields
+                            .iter()
+                            .find(|e| e.name == name)
+                            .and_then(|e| e.unit)
+                            .map(|e| e.to_string())
+                    } else {
+                        fields
+                            .iter()
+                            .find(|e| e.name == name)
+                            .and_then(|e| e.value.as_ref())
+                            .map(|e| format_value(e, spec))
+                    };
+
+                    let value = value.unwrap_or_else(|| placeholder.to_string());
+                    output.push_str(&pad(value, spec));
+                }
+            }
+        }
+
+        output
+    }
+}
+
+fn parse_placeholder(placeholder: &str) -> Result<Part> {
+    let (name, spec) = match placeholder.split_once(':') {
+        Some((name, spec)) => (name.trim(), parse_spec(spec)?),
+        None => (placeholder.trim(), Spec::default()),
+    };
+
+    let field_name = name.strip_suffix(UNIT_SUFFIX).unwrap_or(name);
+    if name != "time" && !WeatherInfoDisplay::FIELD_NAMES.contains(&field_name) {
+        return Err(WeatherError::Parse(format!(
+            "Unknown field '{name}' in template (available: time, {})",
+            WeatherInfoDisplay::FIELD_NAMES.join(", ")
+        )));
+    }
+
+    Ok(Part::Field {
+        name: name.to_string(),
+        spec,
+    })
+}
+
+fn parse_spec(spec: &str) -> Result<Spec> {
+    if spec.contains('%') {
+        // Invalid formats would only fail once rendered.
+        if StrftimeItems::new(spec).any(|e| matches!(e, Item::Error)) {
+            return Err(WeatherError::Parse(format!(
+                "Invalid time format '{spec}' in template"
+            )));
+        }
+        return Ok(Spec {
+            time_format: Some(spec.to_string()),
+            ..Spec::default()
+        });
+    }
+
+    let mut result = Spec::default();
+    let mut rest = spec;
+
+    if let Some(align) = rest.chars().next().filter(|c| matches!(c, '<' | '>' | '^')) {
+        result.align = Some(align);
+        rest = &rest[1..];
+    }
+
+    let (width, precision) = match rest.split_once('.') {
+        Some((width, precision)) => (width, Some(precision)),
+        None => (rest, None),
+    };
+
+    let invalid = || WeatherError::Parse(format!("Invalid format spec '{spec}' in template"));
+
+    if !width.is_empty() {
+        result.width = Some(width.parse().map_err(|_| invalid())?);
+    }
+    if let Some(precision) = precision {
+        result.precision = Some(precision.parse().map_err(|_| invalid())?);
+    }
+
+    Ok(result)
+}
+
+fn format_value(value: &FieldValue, spec: &Spec) -> String {
+    match (value, spec.precision) {
+        (FieldValue::Number(value), Some(precision)) => format!("{value:.precision$}"),
+        (FieldValue::Integer(value), Some(precision)) if precision > 0 => {
+            format!("{:.precision$}", *value as f64)
+        }
+        (FieldValue::Text(value), Some(precision)) => value.chars().take(precision).collect(),
+        (FieldValue::Time(value), _) => match &spec.time_format {
+            Some(format) => value.format(format).to_string(),
+            None => value.to_rfc3339_opts(SecondsFormat::Secs, true),
+        },
+        (value, _) => value.to_string(),
+    }
+}
+
+fn pad(value: String, spec: &Spec) -> String {
+    let width = match spec.width {
+        Some(width) => width,
+        None => return value,
+    };
+
+    match spec.align {
+        Some('>') => format!("{value:>width$}"),
+        Some('^') => format!("{value:^width$}"),
+        _ => format!("{value:<width$}"),
+    }
+}
@@ -1,4 +1,7 @@
 use crate::arguments::DataStorage;
+use crate::cache::CacheStorage;
+use crate::geocoder::GeocoderFactory;
+use crate::processor::LocationPicker;
 use crate::provider::ProviderFactory;
 
 /// Processor dependencies factory.
@@ -10,4 +13,13 @@ pub trait DependencyFactory {
 
     /// Makes implementation-specific provider factory.
     fn make_provider_factory(&self) -> Box<dyn ProviderFactory>;
+
+    /// Makes implementation-specific geocoder factory.
+    fn make_geocoder_factory(&self) -> Box<dyn GeocoderFactory>;
+
+    /// Makes implementation-specific cache storage.
+    fn make_cache_storage(&self) -> Box<dyn CacheStorage>;
+
+    /// Makes implementation-specific picker of ambiguous locations.
+    fn make_location_picker(&self) -> Box<dyn LocationPicker>;
 }
@@ -0,0 +1,11 @@
+use crate::error::Result;
+use crate::geocoder::Location;
+
+/// Chooses one of the ambiguous locations.
+pub trait LocationPicker {
+    /// Asks to choose one of the locations matching the address.
+    ///
+    /// Returns index of the chosen location, `None` if choosing is not possible
+    /// (e.g. non-interactive session).
+    fn pick(&self, address: &str, locations: &[Location]) -> Result<Option<usize>>;
+}
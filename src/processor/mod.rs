@@ -1,35 +1,80 @@
 mod dependency_factory;
+mod location_picker;
 mod production_dependency_factory;
+mod production_location_picker;
 
 pub use dependency_factory::DependencyFactory;
+pub use location_picker::LocationPicker;
 pub use production_dependency_factory::ProductionDependencyFactory;
+pub use production_location_picker::ProductionLocationPicker;
 
-use crate::arguments;
+use crate::arguments::{self, Address, DataStorage};
+use crate::cache::{CacheMode, CacheStorage, CachingGeocoder, CachingProvider};
+use crate::error::{Result, WeatherError};
+use crate::geocoder::{FallbackGeocoder, Geocoder, Location};
+use crate::output::{self, Output, OutputFormat};
+use crate::provider::{self, FallbackProvider, Provider, WeatherInfo};
 
-use std::error::Error;
-use string_error::into_err;
+use chrono::{DateTime, Utc};
+use std::cell::Cell;
+use std::panic;
+use std::sync::Arc;
+use std::thread;
+
+/// Providers the weather is requested from, never empty.
+struct Providers {
+    /// Name of the provider or the provider group
+    name: String,
+
+    /// Configs of the providers in the order they are tried
+    configs: Vec<arguments::WeatherProvider>,
+}
 
 /// CLI processor.
 pub struct Processor {
     dependency_factory: Box<dyn dependency_factory::DependencyFactory>,
+
+    /// Whether expired stored data was shown
+    stale: Cell<bool>,
 }
 
 impl Processor {
     /// Creates a new Processor with the specified dependency factory.
     pub fn new(dependency_factory: Box<dyn dependency_factory::DependencyFactory>) -> Self {
-        Self { dependency_factory }
+        Self {
+            dependency_factory,
+            stale: Cell::new(false),
+        }
+    }
+
+    /// Checks whether expired stored data was shown instead of fresh data,
+    /// e.g. offline or because the provider could not be reached.
+    pub fn served_stale(&self) -> bool {
+        self.stale.get()
     }
 
     /// Performs CLI processing.
-    pub fn run(&self, cli: arguments::Cli) -> Result<(), Box<dyn Error>> {
+    pub fn run(&self, cli: arguments::Cli) -> Result<()> {
         let data_storage = self.dependency_factory.make_data_storage();
 
         match cli.command {
             arguments::WeatherCommand::Provider(provider) => match provider.command {
                 arguments::ProviderSubcommand::Add(provider) => {
+                    if provider.api_key.is_none() && provider.provider.requires_api_key() {
+                        return Err(WeatherError::Config(format!(
+                            "Provider {} requires an API key",
+                            provider.provider
+                        )));
+                    }
+                    if provider.contact.is_none() && provider.provider.requires_contact() {
+                        return Err(WeatherError::Config(format!(
+                            "Provider {} requires a contact, such as an email address",
+                            provider.provider
+                        )));
+                    }
                     match data_storage.get_provider(&provider.name) {
                         Ok(provider) => {
-                            return Err(into_err(format!(
+                            return Err(WeatherError::Config(format!(
                                 "Provider '{}' already exists",
                                 provider.name
                             )))
@@ -38,28 +83,25 @@ impl Processor {
                             Ok(_) => {
                                 println!("Successfully added '{}' provider", provider.name)
                             }
-                            Err(e) => {
-                                return Err(into_err(format!("Failed to add new provider ({e})")))
-                            }
+                            Err(e) => return Err(e.context("Failed to add new provider")),
                         },
                     }
                 }
                 arguments::ProviderSubcommand::Remove { name } => {
+                    Self::check_unused(data_storage.as_ref(), &name)
+                        .map_err(|e| e.context(format!("Failed to remove provider '{name}'")))?;
+
                     match data_storage.remove_provider(&name) {
                         Ok(_) => println!("Successfully removed '{name}' provider"),
                         Err(e) => {
-                            return Err(into_err(format!(
-                                "Failed to remove provider '{name}' ({e})"
-                            )))
+                            return Err(e.context(format!("Failed to remove provider '{name}'")))
                         }
                     }
                 }
                 arguments::ProviderSubcommand::Show { name } => match name {
                     Some(name) => match data_storage.get_provider(&name) {
                         Ok(provider) => println!("{}", provider),
-                        Err(e) => {
-                            return Err(into_err(format!("Provider '{name}' not found ({e})")))
-                        }
+                        Err(e) => return Err(e.context(format!("Provider '{name}' not found"))),
                     },
                     None => match data_storage.get_all_providers() {
                         Ok(all_providers) => {
@@ -67,35 +109,788 @@ impl Processor {
                                 println!("{e}\n");
                             }
                         }
-                        Err(e) => return Err(into_err(format!("Failed to get providers ({e})"))),
+                        Err(e) => return Err(e.context("Failed to get providers")),
+                    },
+                },
+                arguments::ProviderSubcommand::Default { name } => {
+                    match data_storage.set_default_provider(&name) {
+                        Ok(_) => println!("Successfully set '{name}' as default provider"),
+                        Err(e) => {
+                            return Err(
+                                e.context(format!("Failed to set default provider '{name}'"))
+                            )
+                        }
+                    }
+                }
+            },
+
+            arguments::WeatherCommand::Group(group) => match group.command {
+                arguments::GroupSubcommand::Add(group) => {
+                    if data_storage.get_provider(&group.name).is_ok()
+                        || data_storage.get_group(&group.name).is_ok()
+                    {
+                        return Err(WeatherError::Config(format!(
+                            "Provider or group '{}' already exists",
+                            group.name
+                        )));
+                    }
+                    for provider in &group.providers {
+                        data_storage
+                            .get_provider(provider)
+                            .map_err(|e| e.context(format!("Provider '{provider}' not found")))?;
+                    }
+
+                    match data_storage.add_group(&group) {
+                        Ok(_) => println!("Successfully added '{}' group", group.name),
+                        Err(e) => return Err(e.context("Failed to add new group")),
+                    }
+                }
+                arguments::GroupSubcommand::Remove { name } => {
+                    Self::check_unused(data_storage.as_ref(), &name)
+                        .map_err(|e| e.context(format!("Failed to remove group '{name}'")))?;
+
+                    match data_storage.remove_group(&name) {
+                        Ok(_) => println!("Successfully removed '{name}' group"),
+                        Err(e) => return Err(e.context(format!("Failed to remove group '{name}'"))),
+                    }
+                }
+                arguments::GroupSubcommand::Show { name } => match name {
+                    Some(name) => match data_storage.get_group(&name) {
+                        Ok(group) => println!("{group}"),
+                        Err(e) => return Err(e.context(format!("Group '{name}' not found"))),
+                    },
+                    None => match data_storage.get_all_groups() {
+                        Ok(groups) => {
+                            for e in groups {
+                                println!("{e}\n");
+                            }
+                        }
+                        Err(e) => return Err(e.context("Failed to get groups")),
                     },
                 },
             },
 
+            arguments::WeatherCommand::Location(location) => match location.command {
+                arguments::LocationSubcommand::Add {
+                    name,
+                    address,
+                    provider,
+                } => {
+                    if data_storage.get_location(&name).is_ok() {
+                        return Err(WeatherError::Config(format!(
+                            "Location '{name}' already exists"
+                        )));
+                    }
+
+                    // Coordinates need no geocoding, so no provider either.
+                    let location = match address.address()? {
+                        Address::Coordinates {
+                            latitude,
+                            longitude,
+                        } => {
+                            if let Some(provider) = &provider {
+                                Self::find_providers(data_storage.as_ref(), provider).map_err(
+                                    |e| e.context(format!("Provider '{provider}' not found")),
+                                )?;
+                            }
+                            Location {
+                                name: name.clone(),
+                                latitude,
+                                longitude,
+                                ..Location::default()
+                            }
+                        }
+                        _ => {
+                            let providers =
+                                Self::get_providers(data_storage.as_ref(), provider.clone())?;
+                            self.resolve_location(
+                                data_storage.as_ref(),
+                                &providers,
+                                &address,
+                                CacheMode::Use,
+                            )?
+                        }
+                    };
+
+                    let location = arguments::SavedLocation {
+                        name,
+                        location,
+                        provider,
+                    };
+                    match data_storage.add_location(&location) {
+                        Ok(_) => println!(
+                            "Successfully added '{}' location ({})",
+                            location.name, location.location
+                        ),
+                        Err(e) => return Err(e.context("Failed to add new location")),
+                    }
+                }
+                arguments::LocationSubcommand::Remove { name } => {
+                    match data_storage.remove_location(&name) {
+                        Ok(_) => println!("Successfully removed '{name}' location"),
+                        Err(e) => {
+                            return Err(e.context(format!("Failed to remove location '{name}'")))
+                        }
+                    }
+                }
+                arguments::LocationSubcommand::Show { name } => {
+                    match data_storage.get_location(&name) {
+                        Ok(location) => println!("{location}"),
+                        Err(e) => return Err(e.context(format!("Location '{name}' not found"))),
+                    }
+                }
+                arguments::LocationSubcommand::List => match data_storage.get_all_locations() {
+                    Ok(locations) => {
+                        for e in locations {
+                            match e.provider {
+                                Some(provider) => {
+                                    println!("{}: {} [{provider}]", e.name, e.location)
+                                }
+                                None => println!("{}: {}", e.name, e.location),
+                            }
+                        }
+                    }
+                    Err(e) => return Err(e.context("Failed to get locations")),
+                },
+            },
+
+            arguments::WeatherCommand::Geocoder(geocoder) => match geocoder.command {
+                arguments::GeocoderSubcommand::Provider => {
+                    match data_storage.set_geocoder(&arguments::GeocoderConfig::Provider) {
+                        Ok(_) => println!("Successfully set provider geocoder"),
+                        Err(e) => return Err(e.context("Failed to set geocoder")),
+                    }
+                }
+                arguments::GeocoderSubcommand::Geonames { path } => {
+                    let path = path.canonicalize().map_err(|e| {
+                        WeatherError::Config(format!(
+                            "GeoNames dump '{}' not found ({e})",
+                            path.display()
+                        ))
+                    })?;
+                    match data_storage.set_geocoder(&arguments::GeocoderConfig::GeoNames { path }) {
+                        Ok(_) => println!("Successfully set GeoNames geocoder"),
+                        Err(e) => return Err(e.context("Failed to set geocoder")),
+                    }
+                }
+                arguments::GeocoderSubcommand::Show => match data_storage.get_geocoder() {
+                    Ok(geocoder) => println!("{geocoder}"),
+                    Err(e) => return Err(e.context("Failed to get geocoder")),
+                },
+            },
+
+            arguments::WeatherCommand::Cache(cache) => {
+                let cache_storage = self.dependency_factory.make_cache_storage();
+                match cache.command {
+                    arguments::CacheSubcommand::Stats => match cache_storage.stats() {
+                        Ok(stats) => println!("{stats}"),
+                        Err(e) => return Err(e.context("Failed to get cache stats")),
+                    },
+                    arguments::CacheSubcommand::Clear { provider: None } => {
+                        match cache_storage.clear() {
+                            Ok(entries) => {
+                                println!("Successfully removed {entries} cached responses")
+                            }
+                            Err(e) => return Err(e.context("Failed to clear cache")),
+                        }
+                    }
+                    arguments::CacheSubcommand::Clear {
+                        provider: Some(name),
+                    } => match Self::clear_provider_cache(
+                        data_storage.as_ref(),
+                        cache_storage.as_ref(),
+                        &name,
+                    ) {
+                        Ok(entries) => {
+                            println!("Successfully removed {entries} cached responses of '{name}'")
+                        }
+                        Err(e) => return Err(e.context("Failed to clear cache")),
+                    },
+                }
+            }
+
             arguments::WeatherCommand::Get {
                 address,
                 date,
                 provider_name,
+                units,
+                output,
+                cache,
             } => {
-                let provider_config = match data_storage.get_provider(&provider_name) {
-                    Ok(provider) => provider,
-                    Err(e) => {
-                        return Err(into_err(format!(
-                            "Provider '{provider_name}' not found ({e})"
-                        )));
+                let date = Self::parse_date(date)?;
+
+                let output = output.output()?;
+                let (providers, location) =
+                    self.resolve(data_storage.as_ref(), provider_name, &address, cache.mode())?;
+                let worker = self.make_provider(&providers, cache.mode());
+
+                let weather = match date {
+                    Some(date) if provider::is_past(date) => worker.get_history(&location, date),
+                    _ => worker.get_weather(&location, date),
+                };
+
+                match weather {
+                    Ok(weather) => {
+                        // Coordinates are labeled with the nearest named place if it can be found.
+                        let nearest_place = match address.address()? {
+                            Address::Coordinates { .. } => self.reverse_geocode(
+                                data_storage.as_ref(),
+                                &providers,
+                                &location,
+                                cache.mode(),
+                            ),
+                            _ => None,
+                        };
+
+                        self.report_source(&worker, &providers, &output);
+                        if let Some(place) = nearest_place {
+                            print!(
+                                "{}",
+                                output::render_nearest_place(
+                                    &place,
+                                    place.distance(location.latitude, location.longitude),
+                                    units.units(),
+                                    &output
+                                )
+                            );
+                        }
+                        print!(
+                            "{}",
+                            output::render_weather(
+                                &weather,
+                                // Stored current conditions refer to the time they were stored.
+                                date.or_else(|| worker.stale_since())
+                                    .unwrap_or_else(Utc::now),
+                                units.units(),
+                                &output
+                            )?
+                        )
                     }
+                    Err(e) => return Err(e.context("Failed to get weather")),
+                }
+            }
+
+            arguments::WeatherCommand::Forecast {
+                address,
+                days,
+                hourly,
+                provider_name,
+                units,
+                output,
+                cache,
+            } => {
+                let output = output.output()?;
+                let (providers, location) =
+                    self.resolve(data_storage.as_ref(), provider_name, &address, cache.mode())?;
+                let worker = self.make_provider(&providers, cache.mode());
+
+                match worker.get_forecast(&location, days, hourly) {
+                    Ok(forecast) => {
+                        self.report_source(&worker, &providers, &output);
+                        print!(
+                            "{}",
+                            output::render_forecast(&forecast, units.units(), &output)?
+                        )
+                    }
+                    Err(e) => return Err(e.context("Failed to get forecast")),
+                }
+            }
+
+            arguments::WeatherCommand::Compare {
+                address,
+                date,
+                providers,
+                units,
+            } => {
+                let date = Self::parse_date(date)?;
+                let providers = Self::get_compared_providers(data_storage.as_ref(), &providers)?;
+
+                let saved_location = match &address.address {
+                    Some(name) => data_storage.get_location(name).ok(),
+                    None => None,
+                };
+                let location = match saved_location {
+                    Some(saved_location) => saved_location.location,
+                    None => self.resolve_location(
+                        data_storage.as_ref(),
+                        &providers,
+                        &address,
+                        CacheMode::Use,
+                    )?,
                 };
 
-                let provider_factory = self.dependency_factory.make_provider_factory();
-                let worker = provider_factory.make_provider(&provider_config);
+                let results = self.compare(&providers, &location, date);
+                print!("{}", output::render_comparison(&results, units.units()));
 
-                match worker.get_weather(address, date) {
-                    Ok(weather) => println!("{weather}"),
-                    Err(e) => println!("{e}"),
+                if results.iter().all(|(_, result)| result.is_err()) {
+                    // Failures are listed with the results, the first one tells the kind.
+                    if let Some((_, Err(e))) = results.into_iter().next() {
+                        return Err(e.context("No provider answered"));
+                    }
                 }
             }
         }
 
         Ok(())
     }
+
+    /// Parses the requested date relative to now.
+    fn parse_date(date: Option<String>) -> Result<Option<DateTime<Utc>>> {
+        match date {
+            Some(date) => arguments::parse_date(&date, Utc::now())
+                .map(Some)
+                .map_err(|e| e.context("Invalid date")),
+            None => Ok(None),
+        }
+    }
+
+    /// Gets configs of the providers to compare, members of the groups included.
+    ///
+    /// All configured providers are compared if none are specified.
+    fn get_compared_providers(
+        data_storage: &dyn DataStorage,
+        names: &[String],
+    ) -> Result<Providers> {
+        let mut configs: Vec<arguments::WeatherProvider> = Vec::new();
+        if names.is_empty() {
+            configs = data_storage
+                .get_all_providers()
+                .map_err(|e| e.context("Failed to get providers"))?;
+        }
+        for name in names {
+            let providers = Self::find_providers(data_storage, name)
+                .map_err(|e| e.context(format!("Provider '{name}' not found")))?;
+            for config in providers.configs {
+                // Providers of several groups are requested once.
+                if !configs.iter().any(|e| e.name == config.name) {
+                    configs.push(config);
+                }
+            }
+        }
+
+        if configs.is_empty() {
+            return Err(WeatherError::Config(
+                "No providers configured, add one with 'weather provider add'".to_string(),
+            ));
+        }
+
+        Ok(Providers {
+            name: configs
+                .iter()
+                .map(|e| e.name.as_str())
+                .collect::<Vec<_>>()
+                .join(","),
+            configs,
+        })
+    }
+
+    /// Requests the weather from every provider concurrently, bypassing the cache
+    /// to compare what the providers report now.
+    ///
+    /// Results are in the order of the providers.
+    fn compare(
+        &self,
+        providers: &Providers,
+        location: &Location,
+        date: Option<DateTime<Utc>>,
+    ) -> Vec<(String, Result<WeatherInfo>)> {
+        let provider_factory = self.dependency_factory.make_provider_factory();
+        let provider_factory = provider_factory.as_ref();
+        let cache_storage: Arc<dyn CacheStorage> =
+            Arc::from(self.dependency_factory.make_cache_storage());
+        let cache_storage = &cache_storage;
+
+        thread::scope(|scope| {
+            let requests: Vec<_> = providers
+                .configs
+                .iter()
+                .map(|config| {
+                    let request = scope.spawn(move || {
+                        let provider = provider_factory.make_provider(
+                            config,
+                            Box::new(Arc::clone(cache_storage)),
+                            CacheMode::Bypass,
+                        );
+                        match date {
+                            Some(date) if provider::is_past(date) => {
+                                provider.get_history(location, date)
+                            }
+                            _ => provider.get_weather(location, date),
+                        }
+                    });
+                    (config.name.clone(), request)
+                })
+                .collect();
+
+            requests
+                .into_iter()
+                .map(|(name, request)| {
+                    let result = request.join().unwrap_or_else(|e| panic::resume_unwind(e));
+                    (name, result)
+                })
+                .collect()
+        })
+    }
+
+    /// Resolves the providers and the location the weather is requested for.
+    ///
+    /// Saved locations take precedence over geocoding and bring their provider,
+    /// unless another one is specified.
+    fn resolve(
+        &self,
+        data_storage: &dyn DataStorage,
+        provider_name: Option<String>,
+        address: &arguments::AddressArgs,
+        cache_mode: CacheMode,
+    ) -> Result<(Providers, Location)> {
+        let saved_location = match &address.address {
+            Some(name) => data_storage.get_location(name).ok(),
+            None => None,
+        };
+
+        let provider_name =
+            provider_name.or_else(|| saved_location.as_ref().and_then(|e| e.provider.clone()));
+        let providers = Self::get_providers(data_storage, provider_name)?;
+
+        let location = match saved_location {
+            Some(saved_location) => saved_location.location,
+            None => self.resolve_location(data_storage, &providers, address, cache_mode)?,
+        };
+
+        Ok((providers, location))
+    }
+
+    /// Gets configs of the provider or the provider group configured under the specified name.
+    ///
+    /// Falls back to the default provider if no name is specified,
+    /// or to the only configured provider if there is no default one.
+    fn get_providers(
+        data_storage: &dyn DataStorage,
+        provider_name: Option<String>,
+    ) -> Result<Providers> {
+        match provider_name {
+            Some(provider_name) => Self::find_providers(data_storage, &provider_name)
+                .map_err(|e| e.context(format!("Provider '{provider_name}' not found"))),
+            None => Self::get_default_provider(data_storage),
+        }
+    }
+
+    /// Finds the provider or the provider group by name, provider names take precedence.
+    fn find_providers(data_storage: &dyn DataStorage, name: &str) -> Result<Providers> {
+        let provider_error = match data_storage.get_provider(name) {
+            Ok(config) => {
+                return Ok(Providers {
+                    name: name.to_string(),
+                    configs: vec![config],
+                })
+            }
+            Err(e) => e,
+        };
+
+        let group = match data_storage.get_group(name) {
+            Ok(group) => group,
+            Err(_) => return Err(provider_error),
+        };
+        if group.providers.is_empty() {
+            return Err(WeatherError::Config(format!(
+                "Group '{name}' has no providers"
+            )));
+        }
+
+        let configs = group
+            .providers
+            .iter()
+            .map(|provider| {
+                data_storage.get_provider(provider).map_err(|e| {
+                    e.context(format!("Provider '{provider}' of group '{name}' not found"))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Providers {
+            name: name.to_string(),
+            configs,
+        })
+    }
+
+    /// Makes weather provider trying the providers in turn,
+    /// caching its responses unless bypassed.
+    fn make_provider(
+        &self,
+        providers: &Providers,
+        cache_mode: CacheMode,
+    ) -> CachingProvider<FallbackProvider> {
+        let provider_factory = self.dependency_factory.make_provider_factory();
+        let cache_storage: Arc<dyn CacheStorage> =
+            Arc::from(self.dependency_factory.make_cache_storage());
+        let fallback = FallbackProvider::new(
+            providers
+                .configs
+                .iter()
+                .map(|config| {
+                    let provider = provider_factory.make_provider(
+                        config,
+                        Box::new(Arc::clone(&cache_storage)),
+                        cache_mode,
+                    );
+                    (config.name.clone(), provider)
+                })
+                .collect(),
+        );
+
+        CachingProvider::new(
+            fallback,
+            &providers.name,
+            Box::new(cache_storage),
+            cache_mode,
+        )
+    }
+
+    /// Labels the data shown with its age if expired stored data was served,
+    /// and with the provider answered if a group was requested.
+    ///
+    /// Machine-readable output is kept intact, the labels go to stderr then.
+    fn report_source(
+        &self,
+        worker: &CachingProvider<FallbackProvider>,
+        providers: &Providers,
+        output: &Output,
+    ) {
+        let mut notices = Vec::new();
+        if providers.configs.len() > 1 {
+            if let Some(answered) = worker.answered() {
+                notices.push(format!("Provided by {answered}"));
+            }
+        }
+        if let Some(stored_at) = worker.stale_since() {
+            notices.push(output::render_stale_notice(stored_at, Utc::now()));
+            self.stale.set(true);
+        }
+
+        for notice in notices {
+            match output {
+                Output::Format(OutputFormat::Text) => println!("{notice}"),
+                _ => eprintln!("{notice}"),
+            }
+        }
+    }
+
+    /// Makes the configured geocoder, caching results of the provider one unless bypassed.
+    ///
+    /// Provider geocoders of a group are tried in turn like the providers themselves.
+    /// Offline geocoders are not cached as they are not rate limited.
+    fn make_geocoder(
+        &self,
+        data_storage: &dyn DataStorage,
+        providers: &Providers,
+        cache_mode: CacheMode,
+    ) -> Result<Box<dyn Geocoder>> {
+        let geocoder_config = data_storage
+            .get_geocoder()
+            .map_err(|e| e.context("Failed to get geocoder config"))?;
+        let geocoder_factory = self.dependency_factory.make_geocoder_factory();
+
+        let geocoder = match geocoder_config {
+            arguments::GeocoderConfig::Provider if providers.configs.len() > 1 => {
+                Box::new(FallbackGeocoder::new(
+                    providers
+                        .configs
+                        .iter()
+                        .map(|config| geocoder_factory.make_geocoder(&geocoder_config, config))
+                        .collect(),
+                ))
+            }
+            _ => geocoder_factory.make_geocoder(&geocoder_config, &providers.configs[0]),
+        };
+
+        match (&geocoder_config, cache_mode) {
+            // Offline locations are resolved from the cache only.
+            (
+                arguments::GeocoderConfig::Provider,
+                CacheMode::Use | CacheMode::Refresh | CacheMode::Offline,
+            ) => Ok(Box::new(CachingGeocoder::new(
+                geocoder,
+                &providers.name,
+                self.dependency_factory.make_cache_storage(),
+                cache_mode,
+            ))),
+            _ => Ok(geocoder),
+        }
+    }
+
+    /// Checks that no group or saved location refers to the provider or group, so that it can be
+    /// removed without leaving them broken.
+    fn check_unused(data_storage: &dyn DataStorage, name: &str) -> Result<()> {
+        let groups = data_storage
+            .get_all_groups()
+            .map_err(|e| e.context("Failed to get groups list"))?;
+        let locations = data_storage
+            .get_all_locations()
+            .map_err(|e| e.context("Failed to get locations list"))?;
+
+        let referrers = groups
+            .iter()
+            .filter(|e| e.providers.iter().any(|provider| provider == name))
+            .map(|e| format!("group '{}'", e.name))
+            .chain(
+                locations
+                    .iter()
+                    .filter(|e| e.provider.as_deref() == Some(name))
+                    .map(|e| format!("location '{}'", e.name)),
+            )
+            .collect::<Vec<_>>();
+        if referrers.is_empty() {
+            return Ok(());
+        }
+
+        Err(WeatherError::Config(format!(
+            "'{name}' is used by {}, remove them first",
+            referrers.join(", ")
+        )))
+    }
+
+    /// Removes the responses cached for the provider or group, along with the ones the provider
+    /// keeps for all providers of its kind.
+    ///
+    /// Responses of providers removed from the config are cleared by their names as well.
+    fn clear_provider_cache(
+        data_storage: &dyn DataStorage,
+        cache_storage: &dyn CacheStorage,
+        name: &str,
+    ) -> Result<usize> {
+        let mut entries = cache_storage.clear_scope(name)?;
+        if let Ok(config) = data_storage.get_provider(name) {
+            entries += cache_storage.clear_scope(config.provider.cache_scope())?;
+        }
+        Ok(entries)
+    }
+
+    /// Finds the named place nearest to the location, failures are not fatal.
+    fn reverse_geocode(
+        &self,
+        data_storage: &dyn DataStorage,
+        providers: &Providers,
+        location: &Location,
+        cache_mode: CacheMode,
+    ) -> Option<Location> {
+        self.make_geocoder(data_storage, providers, cache_mode)
+            .ok()?
+            .reverse(location.latitude, location.longitude)
+            .ok()
+            .flatten()
+    }
+
+    /// Resolves address to the location with the configured geocoder.
+    ///
+    /// Coordinates are used as is, without geocoding. Ambiguous candidates are narrowed down
+    /// with the country and region, the rest is offered to pick from if possible.
+    fn resolve_location(
+        &self,
+        data_storage: &dyn DataStorage,
+        providers: &Providers,
+        address_args: &arguments::AddressArgs,
+        cache_mode: CacheMode,
+    ) -> Result<Location> {
+        let address = address_args.address()?;
+
+        if let Address::Coordinates {
+            latitude,
+            longitude,
+        } = address
+        {
+            return Ok(Location {
+                name: address.to_string(),
+                latitude,
+                longitude,
+                ..Location::default()
+            });
+        }
+
+        let geocoder = self.make_geocoder(data_storage, providers, cache_mode)?;
+
+        let locations = match &address {
+            Address::Name(name) => geocoder.search(name),
+            Address::Coordinates { .. } => unreachable!("coordinates are not geocoded"),
+            Address::PostalCode { code, country } => {
+                geocoder.search_postal_code(code, country.as_deref())
+            }
+            Address::Id(id) => geocoder.get_location(id).map(|e| vec![e]),
+        };
+
+        let locations =
+            locations.map_err(|e| e.context(format!("Failed to resolve location '{address}'")))?;
+
+        let mut candidates: Vec<Location> = Vec::new();
+        for location in locations {
+            let duplicate = candidates.iter().any(|e| {
+                e.name == location.name
+                    && e.country == location.country
+                    && e.admin_area == location.admin_area
+            });
+            if !duplicate
+                && location.matches(
+                    address_args.country.as_deref(),
+                    address_args.region.as_deref(),
+                )
+            {
+                candidates.push(location);
+            }
+        }
+
+        match candidates.len() {
+            0 => Err(WeatherError::NotFound(format!(
+                "Unknown location '{address}'"
+            ))),
+            1 => Ok(candidates.remove(0)),
+            _ => {
+                let picker = self.dependency_factory.make_location_picker();
+                match picker.pick(&address.to_string(), &candidates)? {
+                    Some(index) if index < candidates.len() => Ok(candidates.swap_remove(index)),
+                    _ => {
+                        let list: Vec<String> =
+                            candidates.iter().map(|e| format!("  {e}")).collect();
+                        Err(WeatherError::NotFound(format!(
+                            "Ambiguous location '{address}', narrow it down with \
+                             --country/--region or use coordinates:\n{}",
+                            list.join("\n")
+                        )))
+                    }
+                }
+            }
+        }
+    }
+
+    /// Gets the default provider or provider group, or the only configured provider.
+    fn get_default_provider(data_storage: &dyn DataStorage) -> Result<Providers> {
+        let default_provider = data_storage
+            .get_default_provider()
+            .map_err(|e| e.context("Failed to get default provider"))?;
+
+        if let Some(provider_name) = default_provider {
+            return Self::find_providers(data_storage, &provider_name)
+                .map_err(|e| e.context(format!("Default provider '{provider_name}' not found")));
+        }
+
+        let mut providers = data_storage
+            .get_all_providers()
+            .map_err(|e| e.context("Failed to get providers"))?;
+
+        match providers.len() {
+            1 => {
+                let config = providers.remove(0);
+                Ok(Providers {
+                    name: config.name.clone(),
+                    configs: vec![config],
+                })
+            }
+            0 => Err(WeatherError::Config(
+                "No providers configured, add one with 'weather provider add'".to_string(),
+            )),
+            _ => Err(WeatherError::Config(
+                "No provider specified and no default configured, \
+                 set one with 'weather provider default'"
+                    .to_string(),
+            )),
+        }
+    }
 }
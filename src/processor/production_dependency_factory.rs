@@ -1,11 +1,18 @@
 use crate::processor::dependency_factory;
+use crate::processor::{LocationPicker, ProductionLocationPicker};
 
 use crate::provider::ProductionProviderFactory;
 use crate::provider::ProviderFactory;
 
+use crate::geocoder::GeocoderFactory;
+use crate::geocoder::ProductionGeocoderFactory;
+
 use crate::arguments::DataStorage;
 use crate::arguments::ProductionDataStorage;
 
+use crate::cache::CacheStorage;
+use crate::cache::ProductionCacheStorage;
+
 /// Processor dependencies factory to be used in production.
 pub struct ProductionDependencyFactory;
 
@@ -19,4 +26,19 @@ impl dependency_factory::DependencyFactory for ProductionDependencyFactory {
     fn make_provider_factory(&self) -> Box<dyn ProviderFactory> {
         Box::new(ProductionProviderFactory)
     }
+
+    /// Makes production geocoder factory
+    fn make_geocoder_factory(&self) -> Box<dyn GeocoderFactory> {
+        Box::new(ProductionGeocoderFactory)
+    }
+
+    /// Makes production cache storage
+    fn make_cache_storage(&self) -> Box<dyn CacheStorage> {
+        Box::<ProductionCacheStorage>::default()
+    }
+
+    /// Makes terminal location picker
+    fn make_location_picker(&self) -> Box<dyn LocationPicker> {
+        Box::new(ProductionLocationPicker)
+    }
 }
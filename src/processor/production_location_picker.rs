@@ -0,0 +1,43 @@
+use crate::error::{Result, WeatherError};
+use crate::geocoder::Location;
+use crate::processor::location_picker;
+
+use std::io::{self, BufRead, IsTerminal, Write};
+
+/// Location picker prompting on the terminal, if both stdin and stdout are terminals.
+pub struct ProductionLocationPicker;
+
+impl location_picker::LocationPicker for ProductionLocationPicker {
+    fn pick(&self, address: &str, locations: &[Location]) -> Result<Option<usize>> {
+        if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+            return Ok(None);
+        }
+
+        println!("Multiple locations match '{address}':");
+        for (index, location) in locations.iter().enumerate() {
+            println!("  {}) {location}", index + 1);
+        }
+
+        let mut stdin = io::stdin().lock();
+        loop {
+            print!("Choose location [1-{}]: ", locations.len());
+            io::stdout().flush().map_err(read_failed)?;
+
+            let mut line = String::new();
+            if stdin.read_line(&mut line).map_err(read_failed)? == 0 {
+                return Err(WeatherError::NotFound("No location chosen".to_string()));
+            }
+
+            match line.trim().parse::<usize>() {
+                Ok(choice) if (1..=locations.len()).contains(&choice) => {
+                    return Ok(Some(choice - 1))
+                }
+                _ => println!("Invalid choice '{}'", line.trim()),
+            }
+        }
+    }
+}
+
+fn read_failed(error: io::Error) -> WeatherError {
+    WeatherError::Parse(format!("Failed to read the choice ({error})"))
+}
@@ -1,90 +1,487 @@
-use crate::provider::{Provider, WeatherInfo};
+use crate::provider::{
+    check_forecast_days, forecast_date, from_timestamp, get_json, history_not_supported,
+    parse_json, send_get, Forecast, ForecastEntry, Provider, WeatherInfo,
+};
 
-//use chrono::{DateTime, Duration, Utc};
+use crate::arguments;
+use crate::cache::{self, CacheStorage};
+use crate::error::{Result, WeatherError};
+use crate::geocoder::Location;
+use crate::provider::units::{Length, Pressure, Speed, Temperature};
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use reqwest::header::HeaderMap;
 use reqwest::StatusCode;
-use std::error::Error;
-use string_error::{into_err, static_err};
 
-static LOCATION_API_URL: &str = "http://dataservice.accuweather.com/locations/v1/cities/search";
+static GEOPOSITION_API_URL: &str =
+    "http://dataservice.accuweather.com/locations/v1/cities/geoposition/search";
 static CURRENT_CONDITION_API_URL: &str = "http://dataservice.accuweather.com/currentconditions/v1";
+static HOURLY_FORECAST_API_URL: &str = "http://dataservice.accuweather.com/forecasts/v1/hourly";
+static DAILY_FORECAST_API_URL: &str = "http://dataservice.accuweather.com/forecasts/v1/daily";
+
+/// Hourly forecast periods in hours available for free, longer ones need a paid plan.
+const HOURLY_FORECAST_PERIODS: [u32; 2] = [1, 12];
+
+/// Daily forecast periods in days available for free, longer ones need a paid plan.
+const DAILY_FORECAST_PERIODS: [u32; 2] = [1, 5];
+
+/// Hourly forecast is used for dates within this horizon, daily forecast otherwise.
+const HOURLY_FORECAST_HORIZON_HOURS: u32 =
+    HOURLY_FORECAST_PERIODS[HOURLY_FORECAST_PERIODS.len() - 1];
+
+/// Daily forecast horizon.
+const FORECAST_HORIZON_DAYS: u32 = DAILY_FORECAST_PERIODS[DAILY_FORECAST_PERIODS.len() - 1];
+
+/// Historical conditions are available for this many past hours only.
+const HISTORY_HORIZON_HOURS: u32 = 24;
 
+/// AccuWeather provider.
+///
+/// Location keys found by coordinates are kept in the key cache between runs,
+/// a cached key is forgotten once a request made with it is rejected as an unknown or invalid
+/// location key, as it may be stale.
 pub struct AccuWeather {
     api_key: String,
+    key_cache: Box<dyn CacheStorage>,
 }
 
 impl AccuWeather {
-    pub fn new(api_key: String) -> Self {
-        Self { api_key }
+    pub fn new(api_key: String, key_cache: Box<dyn CacheStorage>) -> Self {
+        Self { api_key, key_cache }
+    }
+
+    /// Gets location key, searching by coordinates unless resolved by AccuWeather geocoder
+    /// or cached.
+    fn get_location_key(&self, location: &Location) -> Result<String> {
+        if let Some(key) = &location.key {
+            return Ok(key.clone());
+        }
+
+        let cache_key = location_key_cache_key(location);
+        if let Some(key) = self.key_cache.get(&cache_key).ok().flatten() {
+            if let Some(key) = key.as_str() {
+                return Ok(key.to_string());
+            }
+        }
+
+        let data = get_json(&format!(
+            "{}?apikey={}&q={},{}",
+            GEOPOSITION_API_URL, self.api_key, location.latitude, location.longitude
+        ))?;
+
+        match data["Key"].as_str() {
+            Some(key) => {
+                // Failing to cache the key only costs another search next time.
+                let _ = self
+                    .key_cache
+                    .put(&cache_key, &key.into(), cache::location_ttl());
+                Ok(key.to_string())
+            }
+            None => Err(WeatherError::NotFound("Unknown location".to_string())),
+        }
+    }
+
+    /// Forgets the cached key of the location after the request made with it was rejected
+    /// as made for an unknown location, other failures tell nothing about the key.
+    fn forget_location_key(&self, location: &Location, error: WeatherError) -> WeatherError {
+        if location.key.is_none() && matches!(error, WeatherError::NotFound(_)) {
+            let _ = self.key_cache.remove(&location_key_cache_key(location));
+        }
+        error
+    }
+
+    fn get_current(&self, location_key: &str) -> Result<WeatherInfo> {
+        let data = get_json_by_key(&format!(
+            "{}/{}?apikey={}&details=true",
+            CURRENT_CONDITION_API_URL, location_key, self.api_key
+        ))?;
+
+        match data.as_array().and_then(|array| array.first()) {
+            Some(condition) => Ok(parse_condition(condition)),
+            None => Err(WeatherError::Parse(
+                "No weather condition received".to_string(),
+            )),
+        }
+    }
+
+    /// Gets observed conditions for the past 24 hours.
+    fn get_historical(&self, location_key: &str) -> Result<Vec<ForecastEntry>> {
+        let data = get_json_by_key(&format!(
+            "{}/{}/historical/{}?apikey={}&details=true",
+            CURRENT_CONDITION_API_URL, location_key, HISTORY_HORIZON_HOURS, self.api_key
+        ))?;
+
+        let entries: Vec<ForecastEntry> = match data.as_array() {
+            Some(array) => array
+                .iter()
+                .filter_map(|e| {
+                    let time = Utc.timestamp_opt(e["EpochTime"].as_i64()?, 0).single()?;
+                    Some(ForecastEntry {
+                        time,
+                        weather: parse_condition(e),
+                    })
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+
+        if entries.is_empty() {
+            return Err(WeatherError::Parse(
+                "No historical weather received".to_string(),
+            ));
+        }
+
+        Ok(entries)
+    }
+
+    /// Gets hourly forecast entries for at least the specified number of hours.
+    fn get_hourly_forecast(&self, location_key: &str, hours: u32) -> Result<Vec<ForecastEntry>> {
+        let period = forecast_period(&HOURLY_FORECAST_PERIODS, hours)?;
+        let data = get_json_by_key(&format!(
+            "{}/{}hour/{}?apikey={}&metric=true&details=true",
+            HOURLY_FORECAST_API_URL, period, location_key, self.api_key
+        ))?;
+
+        let entries: Vec<ForecastEntry> = match data.as_array() {
+            Some(array) => array
+                .iter()
+                .filter_map(|e| {
+                    let time = Utc
+                        .timestamp_opt(e["EpochDateTime"].as_i64()?, 0)
+                        .single()?;
+                    Some(ForecastEntry {
+                        time,
+                        weather: parse_hourly_forecast(e),
+                    })
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+
+        if entries.is_empty() {
+            return Err(WeatherError::Parse("No forecast received".to_string()));
+        }
+
+        Ok(entries)
+    }
+
+    /// Gets daily forecast entries for at least the specified number of days.
+    fn get_daily_forecast(&self, location_key: &str, days: u32) -> Result<Vec<ForecastEntry>> {
+        let period = forecast_period(&DAILY_FORECAST_PERIODS, days)?;
+        let data = get_json_by_key(&format!(
+            "{}/{}day/{}?apikey={}&metric=true&details=true",
+            DAILY_FORECAST_API_URL, period, location_key, self.api_key
+        ))?;
+
+        let entries: Vec<ForecastEntry> = match data["DailyForecasts"].as_array() {
+            Some(array) => array
+                .iter()
+                .filter_map(|e| {
+                    let time = Utc.timestamp_opt(e["EpochDate"].as_i64()?, 0).single()?;
+                    Some(ForecastEntry {
+                        time,
+                        weather: parse_daily_forecast(e),
+                    })
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+
+        if entries.is_empty() {
+            return Err(WeatherError::Parse("No forecast received".to_string()));
+        }
+
+        Ok(entries)
     }
 }
 
+/// Parses current or historical condition.
+fn parse_condition(condition: &serde_json::Value) -> WeatherInfo {
+    let mut info = WeatherInfo::default();
+
+    if let Some(description) = condition["WeatherText"].as_str() {
+        info.description = Some(description.to_string());
+    }
+
+    if let Some(temperature) = condition["Temperature"]["Metric"]["Value"].as_f64() {
+        info.temperature = Some(Temperature::from_celsius(temperature));
+    }
+
+    info.humidity = condition["RelativeHumidity"].as_i64();
+    info.pressure = condition["Pressure"]["Metric"]["Value"]
+        .as_f64()
+        .map(Pressure::from_hectopascals);
+    info.feels_like = condition["RealFeelTemperature"]["Metric"]["Value"]
+        .as_f64()
+        .map(Temperature::from_celsius);
+    info.dew_point = condition["DewPoint"]["Metric"]["Value"]
+        .as_f64()
+        .map(Temperature::from_celsius);
+    info.wind_speed = condition["Wind"]["Speed"]["Metric"]["Value"]
+        .as_f64()
+        .map(Speed::from_kilometers_per_hour);
+    info.wind_direction = condition["Wind"]["Direction"]["Degrees"].as_i64();
+    info.wind_gust = condition["WindGust"]["Speed"]["Metric"]["Value"]
+        .as_f64()
+        .map(Speed::from_kilometers_per_hour);
+    info.precipitation = condition["PrecipitationSummary"]["PastHour"]["Metric"]["Value"]
+        .as_f64()
+        .map(Length::from_millimeters);
+    info.cloud_cover = condition["CloudCover"].as_i64();
+    info.visibility = condition["Visibility"]["Metric"]["Value"]
+        .as_f64()
+        .map(Length::from_kilometers);
+    info.uv_index = condition["UVIndex"].as_f64();
+
+    info
+}
+
+/// Parses hourly forecast requested with metric units.
+fn parse_hourly_forecast(forecast: &serde_json::Value) -> WeatherInfo {
+    WeatherInfo {
+        description: forecast["IconPhrase"].as_str().map(|e| e.to_string()),
+        temperature: forecast["Temperature"]["Value"]
+            .as_f64()
+            .map(Temperature::from_celsius),
+        humidity: forecast["RelativeHumidity"].as_i64(),
+        pressure: None,
+        feels_like: forecast["RealFeelTemperature"]["Value"]
+            .as_f64()
+            .map(Temperature::from_celsius),
+        dew_point: forecast["DewPoint"]["Value"]
+            .as_f64()
+            .map(Temperature::from_celsius),
+        wind_speed: forecast["Wind"]["Speed"]["Value"]
+            .as_f64()
+            .map(Speed::from_kilometers_per_hour),
+        wind_direction: forecast["Wind"]["Direction"]["Degrees"].as_i64(),
+        wind_gust: forecast["WindGust"]["Speed"]["Value"]
+            .as_f64()
+            .map(Speed::from_kilometers_per_hour),
+        precipitation: forecast["TotalLiquid"]["Value"]
+            .as_f64()
+            .map(Length::from_millimeters),
+        precipitation_probability: forecast["PrecipitationProbability"].as_i64(),
+        cloud_cover: forecast["CloudCover"].as_i64(),
+        visibility: forecast["Visibility"]["Value"]
+            .as_f64()
+            .map(Length::from_kilometers),
+        uv_index: forecast["UVIndex"].as_f64(),
+        sunrise: None,
+        sunset: None,
+        air_quality: None,
+    }
+}
+
+/// Parses daily forecast requested with metric units.
+///
+/// Daily forecast has no single values, the mean of min and max is used
+/// for temperatures and the day part is used for the rest.
+fn parse_daily_forecast(forecast: &serde_json::Value) -> WeatherInfo {
+    let mean = |e: &serde_json::Value| match (
+        e["Minimum"]["Value"].as_f64(),
+        e["Maximum"]["Value"].as_f64(),
+    ) {
+        (Some(min), Some(max)) => Some(Temperature::from_celsius((min + max) / 2.0)),
+        (min, max) => min.or(max).map(Temperature::from_celsius),
+    };
+
+    let day = &forecast["Day"];
+
+    let uv_index = forecast["AirAndPollen"].as_array().and_then(|array| {
+        array
+            .iter()
+            .find(|e| e["Name"].as_str() == Some("UVIndex"))
+            .and_then(|e| e["Value"].as_f64())
+    });
+
+    WeatherInfo {
+        description: day["IconPhrase"].as_str().map(|e| e.to_string()),
+        temperature: mean(&forecast["Temperature"]),
+        humidity: day["RelativeHumidity"]["Average"].as_i64(),
+        pressure: None,
+        feels_like: mean(&forecast["RealFeelTemperature"]),
+        dew_point: None,
+        wind_speed: day["Wind"]["Speed"]["Value"]
+            .as_f64()
+            .map(Speed::from_kilometers_per_hour),
+        wind_direction: day["Wind"]["Direction"]["Degrees"].as_i64(),
+        wind_gust: day["WindGust"]["Speed"]["Value"]
+            .as_f64()
+            .map(Speed::from_kilometers_per_hour),
+        precipitation: day["TotalLiquid"]["Value"]
+            .as_f64()
+            .map(Length::from_millimeters),
+        precipitation_probability: day["PrecipitationProbability"].as_i64(),
+        cloud_cover: day["CloudCover"].as_i64(),
+        visibility: None,
+        uv_index,
+        sunrise: from_timestamp(forecast["Sun"]["EpochRise"].as_i64()),
+        sunset: from_timestamp(forecast["Sun"]["EpochSet"].as_i64()),
+        air_quality: None,
+    }
+}
+
+/// Picks the shortest available forecast period covering the requested one.
+fn forecast_period(periods: &[u32], requested: u32) -> Result<u32> {
+    periods
+        .iter()
+        .find(|period| **period >= requested)
+        .copied()
+        .ok_or_else(|| {
+            WeatherError::Unsupported(format!("Forecast period {requested} is not available"))
+        })
+}
+
+/// Performs GET request made with a location key and parses JSON response.
+fn get_json_by_key(url: &str) -> Result<serde_json::Value> {
+    let response = send_get(url, HeaderMap::new())?;
+    match response.status() {
+        StatusCode::OK => parse_json(response),
+        // Unknown location keys are rejected as invalid requests.
+        StatusCode::BAD_REQUEST => Err(WeatherError::NotFound(format!(
+            "Request failed ({}), the location key is invalid",
+            StatusCode::BAD_REQUEST
+        ))),
+        status => Err(WeatherError::from_status(status)),
+    }
+}
+
+/// Key the location key is cached under, AccuWeather location keys do not depend on API keys.
+///
+/// Keys are found by the coordinates the address was geocoded to, so a key is shared by all
+/// addresses resolved to the same point and is only dropped from the cache along with the
+/// whole scope, by `weather cache clear --provider`, or once rejected.
+fn location_key_cache_key(location: &Location) -> String {
+    format!(
+        "location-key|{}|{:.4},{:.4}",
+        arguments::Provider::AccuWeather.cache_scope(),
+        location.latitude,
+        location.longitude
+    )
+}
+
 impl Provider for AccuWeather {
-    fn get_weather(
-        &self,
-        address: String,
-        _date: Option<String>,
-    ) -> Result<WeatherInfo, Box<dyn Error>> {
-        let response = reqwest::blocking::get(format!(
-            "{}?apikey={}&q={}",
-            LOCATION_API_URL, self.api_key, address
-        ))
-        .map_err(|e| into_err(format!("Request failed ({e})")))
-        .and_then(|r| {
-            if r.status() != StatusCode::OK {
-                return Err(into_err(format!("Request failed ({})", r.status())));
+    fn get_weather(&self, location: &Location, date: Option<DateTime<Utc>>) -> Result<WeatherInfo> {
+        let date = forecast_date(date, Duration::days(FORECAST_HORIZON_DAYS.into()))?;
+        let location_key = self.get_location_key(location)?;
+
+        let date = match date {
+            Some(date) => date,
+            None => {
+                return self
+                    .get_current(&location_key)
+                    .map_err(|e| self.forget_location_key(location, e))
             }
-            Ok(r)
-        })?;
-
-        let data = response
-            .json::<serde_json::Value>()
-            .map_err(|e| into_err(format!("Failed to parse response data ({e})")))?;
-
-        let location_key = match data
-            .as_array()
-            .and_then(|array| array.get(0))
-            .and_then(|loc| loc["Key"].as_str())
-        {
-            Some(key) => key,
-            None => return Err(static_err("Unknown location")),
         };
 
-        let response = reqwest::blocking::get(format!(
-            "{}/{}?apikey={}",
-            CURRENT_CONDITION_API_URL, location_key, self.api_key
-        ))
-        .map_err(|e| into_err(format!("Request failed ({e})")))
-        .and_then(|r| {
-            if r.status() != StatusCode::OK {
-                return Err(into_err(format!("Request failed ({})", r.status())));
+        let hourly_horizon = Utc::now() + Duration::hours(HOURLY_FORECAST_HORIZON_HOURS.into());
+        let entry = if date <= hourly_horizon {
+            self.get_hourly_forecast(&location_key, HOURLY_FORECAST_HORIZON_HOURS)
+                .map_err(|e| self.forget_location_key(location, e))?
+                .into_iter()
+                .min_by_key(|e| (e.time - date).num_seconds().abs())
+        } else {
+            // Daily forecast covers the day starting at its time.
+            let mut entries = self
+                .get_daily_forecast(&location_key, FORECAST_HORIZON_DAYS)
+                .map_err(|e| self.forget_location_key(location, e))?;
+            match entries.iter().rposition(|e| e.time <= date) {
+                Some(position) => Some(entries.swap_remove(position)),
+                None => entries.into_iter().next(),
             }
-            Ok(r)
-        })?;
-
-        let data = response
-            .json::<serde_json::Value>()
-            .map_err(|e| into_err(format!("Failed to parse response data ({e})")))?;
-
-        let mut info = WeatherInfo {
-            description: None,
-            temperature: None,
-            humidity: None,
-            pressure: None,
         };
 
-        let condition = match data.as_array().and_then(|array| array.get(0)) {
-            Some(key) => key,
-            None => return Err(static_err("No weather condition received")),
+        match entry {
+            Some(entry) => Ok(entry.weather),
+            None => Err(WeatherError::Parse("No forecast received".to_string())),
+        }
+    }
+
+    /// Hourly forecast covers the first hours of the day only, as longer ones are not free.
+    fn get_forecast(&self, location: &Location, days: u32, hourly: bool) -> Result<Forecast> {
+        let horizon_days = if hourly {
+            HOURLY_FORECAST_HORIZON_HOURS.div_ceil(24)
+        } else {
+            FORECAST_HORIZON_DAYS
+        };
+        check_forecast_days(days, horizon_days)?;
+
+        let location_key = self.get_location_key(location)?;
+
+        let entries = if hourly {
+            self.get_hourly_forecast(&location_key, HOURLY_FORECAST_HORIZON_HOURS)
+                .map_err(|e| self.forget_location_key(location, e))?
+        } else {
+            self.get_daily_forecast(&location_key, days)
+                .map_err(|e| self.forget_location_key(location, e))?
+                .into_iter()
+                .take(days as usize)
+                .collect()
         };
 
-        if let Some(description) = condition["WeatherText"].as_str() {
-            info.description = Some(description.to_string());
+        Ok(Forecast { entries })
+    }
+
+    fn get_history(&self, location: &Location, date: DateTime<Utc>) -> Result<WeatherInfo> {
+        let since = Utc::now() - Duration::hours(HISTORY_HORIZON_HOURS.into());
+        if date < since {
+            return Err(history_not_supported(Some(since)));
         }
 
-        if let Some(temperature) = condition["Temperature"]["Metric"]["Value"].as_f64() {
-            info.temperature = Some(temperature);
+        let location_key = self.get_location_key(location)?;
+
+        let closest = self
+            .get_historical(&location_key)
+            .map_err(|e| self.forget_location_key(location, e))?
+            .into_iter()
+            .min_by_key(|e| (e.time - date).num_seconds().abs());
+
+        match closest {
+            Some(entry) => Ok(entry.weather),
+            None => Err(WeatherError::Parse(
+                "No historical weather received".to_string(),
+            )),
         }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn free_forecast_periods() {
+        assert_eq!(12, forecast_period(&HOURLY_FORECAST_PERIODS, 2).unwrap());
+        assert_eq!(5, forecast_period(&DAILY_FORECAST_PERIODS, 3).unwrap());
+        assert_eq!(
+            Some(9),
+            forecast_period(&DAILY_FORECAST_PERIODS, 10)
+                .err()
+                .map(|e| e.exit_code())
+        );
+    }
+
+    #[test]
+    fn parse_detailed_condition() {
+        let data: serde_json::Value = serde_json::from_str(include_str!(
+            "../../tests/data/accu_weather/current_conditions.json"
+        ))
+        .unwrap();
+        let info = parse_condition(&data[0]);
 
-        Ok(info)
+        assert_eq!(Some("Light rain"), info.description.as_deref());
+        assert_eq!(Some(Temperature::from_celsius(1.5)), info.temperature);
+        assert_eq!(Some(87), info.humidity);
+        assert_eq!(Some(Pressure::from_hectopascals(1004.1)), info.pressure);
+        assert_eq!(Some(Temperature::from_celsius(-2.8)), info.feels_like);
+        assert_eq!(Some(Temperature::from_celsius(-0.5)), info.dew_point);
+        assert_eq!(Some(Speed::from_kilometers_per_hour(18.5)), info.wind_speed);
+        assert_eq!(Some(225), info.wind_direction);
+        assert_eq!(Some(Speed::from_kilometers_per_hour(33.3)), info.wind_gust);
+        assert_eq!(Some(Length::from_millimeters(0.4)), info.precipitation);
+        assert_eq!(Some(100), info.cloud_cover);
+        assert_eq!(Some(Length::from_kilometers(8.0)), info.visibility);
+        assert_eq!(Some(1.0), info.uv_index);
     }
 }
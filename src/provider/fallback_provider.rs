@@ -0,0 +1,63 @@
+use crate::error::{Result, WeatherError};
+use crate::geocoder::Location;
+use crate::provider::{Forecast, Provider, WeatherInfo};
+
+use chrono::{DateTime, Utc};
+use std::cell::RefCell;
+
+/// Provider trying the providers of a group in turn.
+///
+/// The next provider is tried only if the request may succeed with it, i.e. the previous
+/// one could not be reached, failed or ran out of quota, other errors are returned as is.
+pub struct FallbackProvider {
+    providers: Vec<(String, Box<dyn Provider>)>,
+
+    /// Name of the provider answered the last request
+    answered: RefCell<Option<String>>,
+}
+
+impl FallbackProvider {
+    /// Creates the provider trying the named providers in the order given.
+    pub fn new(providers: Vec<(String, Box<dyn Provider>)>) -> Self {
+        Self {
+            providers,
+            answered: RefCell::new(None),
+        }
+    }
+
+    fn try_each<T>(&self, request: impl Fn(&dyn Provider) -> Result<T>) -> Result<T> {
+        let mut last_error = None;
+
+        for (name, provider) in &self.providers {
+            match request(provider.as_ref()) {
+                Ok(value) => {
+                    self.answered.replace(Some(name.clone()));
+                    return Ok(value);
+                }
+                Err(e) if e.is_transient() => last_error = Some(e),
+                Err(e) => return Err(e),
+            }
+        }
+
+        Err(last_error
+            .unwrap_or_else(|| WeatherError::Config("No providers to request".to_string())))
+    }
+}
+
+impl Provider for FallbackProvider {
+    fn get_weather(&self, location: &Location, date: Option<DateTime<Utc>>) -> Result<WeatherInfo> {
+        self.try_each(|provider| provider.get_weather(location, date))
+    }
+
+    fn get_forecast(&self, location: &Location, days: u32, hourly: bool) -> Result<Forecast> {
+        self.try_each(|provider| provider.get_forecast(location, days, hourly))
+    }
+
+    fn get_history(&self, location: &Location, date: DateTime<Utc>) -> Result<WeatherInfo> {
+        self.try_each(|provider| provider.get_history(location, date))
+    }
+
+    fn answered(&self) -> Option<String> {
+        self.answered.borrow().clone()
+    }
+}
@@ -0,0 +1,461 @@
+use crate::provider::{
+    check_forecast_days, closest_entry, daily_entries, forecast_date, parse_json, send_get,
+    Forecast, ForecastEntry, Provider, WeatherInfo, USER_AGENT,
+};
+
+use crate::arguments;
+use crate::cache::{CacheMode, CacheStorage};
+use crate::error::{Result, WeatherError};
+use crate::geocoder::Location;
+use crate::provider::units::{Length, Pressure, Speed, Temperature};
+
+use chrono::{DateTime, Duration, Utc};
+use reqwest::header::{self, HeaderMap, HeaderValue, EXPIRES, IF_MODIFIED_SINCE, LAST_MODIFIED};
+use reqwest::StatusCode;
+
+static LOCATIONFORECAST_API_URL: &str =
+    "https://api.met.no/weatherapi/locationforecast/2.0/complete";
+
+/// Forecast horizon of the Locationforecast, hourly for the first days and 6-hourly later.
+const FORECAST_HORIZON_DAYS: u32 = 9;
+
+/// MET Norway Locationforecast API, free to use under its terms of service.
+///
+/// The terms require requests to identify the client along with a way to contact its user,
+/// coordinates to be truncated to four decimals and a forecast not to be requested again
+/// before it expires. Forecasts are kept in the response cache until the `Expires` time and
+/// revalidated with `If-Modified-Since` then, unless the cache is refreshed or bypassed.
+pub struct MetNorway {
+    contact: Option<String>,
+    response_cache: Box<dyn CacheStorage>,
+    cache_mode: CacheMode,
+}
+
+impl MetNorway {
+    pub fn new(
+        contact: Option<String>,
+        response_cache: Box<dyn CacheStorage>,
+        cache_mode: CacheMode,
+    ) -> Self {
+        Self {
+            contact,
+            response_cache,
+            cache_mode,
+        }
+    }
+
+    /// Gets the forecast data, requesting it only once the stored one expires.
+    fn get_forecast_data(&self, location: &Location) -> Result<serde_json::Value> {
+        let (latitude, longitude) = (truncate(location.latitude), truncate(location.longitude));
+        let cache_key = format!(
+            "locationforecast|{}|{latitude},{longitude}",
+            arguments::Provider::MetNorway.cache_scope()
+        );
+
+        // Cache failures only cost a full request.
+        let stored = match self.cache_mode {
+            CacheMode::Use | CacheMode::Offline => {
+                if let Some(mut stored) = self.response_cache.get(&cache_key).ok().flatten() {
+                    return Ok(stored["data"].take());
+                }
+                self.response_cache
+                    .get_last(&cache_key)
+                    .ok()
+                    .flatten()
+                    .map(|e| e.value)
+            }
+            CacheMode::Refresh | CacheMode::Bypass => None,
+        };
+
+        let mut headers = conditional_headers(stored.as_ref());
+        headers.insert(header::USER_AGENT, self.user_agent()?);
+
+        let response = send_get(
+            &format!("{LOCATIONFORECAST_API_URL}?lat={latitude}&lon={longitude}"),
+            headers,
+        )?;
+        let ttl = time_to_live(response.headers());
+
+        let mut stored = match (response.status(), stored) {
+            (StatusCode::NOT_MODIFIED, Some(stored)) => stored,
+            (status, _) if status.is_success() => {
+                let last_modified = response
+                    .headers()
+                    .get(LAST_MODIFIED)
+                    .and_then(|e| e.to_str().ok())
+                    .map(|e| e.to_string());
+                serde_json::json!({
+                    "last_modified": last_modified,
+                    "data": parse_json(response)?,
+                })
+            }
+            (status, _) => return Err(WeatherError::from_status(status)),
+        };
+
+        if self.cache_mode != CacheMode::Bypass {
+            let _ = self.response_cache.put(&cache_key, &stored, ttl);
+        }
+        Ok(stored["data"].take())
+    }
+
+    /// Builds user agent identifying the application along with the contact of its user.
+    fn user_agent(&self) -> Result<HeaderValue> {
+        let contact = self.contact.as_deref().ok_or_else(|| {
+            WeatherError::Config(
+                "MET Norway requires a contact, add the provider again with --contact".to_string(),
+            )
+        })?;
+
+        HeaderValue::from_str(&format!("{USER_AGENT} ({contact})"))
+            .map_err(|_| WeatherError::Config(format!("Invalid contact '{contact}'")))
+    }
+
+    /// Gets forecast timeseries, hourly entries first and 6-hourly ones later.
+    fn get_entries(&self, location: &Location) -> Result<Vec<ForecastEntry>> {
+        let data = self.get_forecast_data(location)?;
+
+        let entries: Vec<ForecastEntry> = match data["properties"]["timeseries"].as_array() {
+            Some(array) => array.iter().filter_map(parse_entry).collect(),
+            None => Vec::new(),
+        };
+
+        if entries.is_empty() {
+            return Err(WeatherError::Parse("No forecast received".to_string()));
+        }
+
+        Ok(entries)
+    }
+}
+
+/// Truncates coordinate to four decimals.
+fn truncate(coordinate: f64) -> String {
+    // Formatting rounds, so extra digits are formatted to be cut off.
+    let formatted = format!("{coordinate:.8}");
+    formatted[..formatted.len() - 4].to_string()
+}
+
+/// Builds headers revalidating the stored forecast, if it was modified since it was received.
+fn conditional_headers(stored: Option<&serde_json::Value>) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    if let Some(last_modified) = stored
+        .and_then(|e| e["last_modified"].as_str())
+        .and_then(|e| HeaderValue::from_str(e).ok())
+    {
+        headers.insert(IF_MODIFIED_SINCE, last_modified);
+    }
+    headers
+}
+
+/// Gets the time the response may be stored for, until its `Expires` time.
+fn time_to_live(headers: &HeaderMap) -> Duration {
+    headers
+        .get(EXPIRES)
+        .and_then(|e| e.to_str().ok())
+        .and_then(|e| DateTime::parse_from_rfc2822(e).ok())
+        .map(|e| e.with_timezone(&Utc) - Utc::now())
+        .filter(|e| *e > Duration::zero())
+        .unwrap_or_else(Duration::zero)
+}
+
+/// Parses timeseries entry, instant details in metric units along with the summary of the
+/// shortest period following it.
+fn parse_entry(entry: &serde_json::Value) -> Option<ForecastEntry> {
+    let time = DateTime::parse_from_rfc3339(entry["time"].as_str()?)
+        .ok()?
+        .with_timezone(&Utc);
+
+    let data = &entry["data"];
+    let details = &data["instant"]["details"];
+    let period = ["next_1_hours", "next_6_hours", "next_12_hours"]
+        .iter()
+        .map(|e| &data[*e])
+        .find(|e| e.is_object())
+        .unwrap_or(&serde_json::Value::Null);
+
+    Some(ForecastEntry {
+        time,
+        weather: WeatherInfo {
+            description: period["summary"]["symbol_code"]
+                .as_str()
+                .and_then(describe_symbol_code),
+            temperature: details["air_temperature"]
+                .as_f64()
+                .map(Temperature::from_celsius),
+            humidity: details["relative_humidity"]
+                .as_f64()
+                .map(|e| e.round() as i64),
+            pressure: details["air_pressure_at_sea_level"]
+                .as_f64()
+                .map(Pressure::from_hectopascals),
+            feels_like: None,
+            dew_point: details["dew_point_temperature"]
+                .as_f64()
+                .map(Temperature::from_celsius),
+            wind_speed: details["wind_speed"]
+                .as_f64()
+                .map(Speed::from_meters_per_second),
+            wind_direction: details["wind_from_direction"]
+                .as_f64()
+                .map(|e| e.round() as i64),
+            wind_gust: details["wind_speed_of_gust"]
+                .as_f64()
+                .map(Speed::from_meters_per_second),
+            precipitation: period["details"]["precipitation_amount"]
+                .as_f64()
+                .map(Length::from_millimeters),
+            precipitation_probability: period["details"]["probability_of_precipitation"]
+                .as_f64()
+                .map(|e| e.round() as i64),
+            cloud_cover: details["cloud_area_fraction"]
+                .as_f64()
+                .map(|e| e.round() as i64),
+            visibility: None,
+            uv_index: details["ultraviolet_index_clear_sky"].as_f64(),
+            sunrise: None,
+            sunset: None,
+            air_quality: None,
+        },
+    })
+}
+
+/// Describes weather symbol code, such as "lightrainshowers_day".
+fn describe_symbol_code(code: &str) -> Option<String> {
+    // Variants of the time of day share the description.
+    let symbol = code.split('_').next()?;
+
+    let description = match symbol {
+        "clearsky" => "Clear sky",
+        "fair" => "Fair",
+        "partlycloudy" => "Partly cloudy",
+        "cloudy" => "Cloudy",
+        "fog" => "Fog",
+        "lightrainshowers" => "Light rain showers",
+        "rainshowers" => "Rain showers",
+        "heavyrainshowers" => "Heavy rain showers",
+        "lightrainshowersandthunder" => "Light rain showers and thunder",
+        "rainshowersandthunder" => "Rain showers and thunder",
+        "heavyrainshowersandthunder" => "Heavy rain showers and thunder",
+        "lightsleetshowers" => "Light sleet showers",
+        "sleetshowers" => "Sleet showers",
+        "heavysleetshowers" => "Heavy sleet showers",
+        // Misspelt codes are the ones actually sent.
+        "lightssleetshowersandthunder" => "Light sleet showers and thunder",
+        "sleetshowersandthunder" => "Sleet showers and thunder",
+        "heavysleetshowersandthunder" => "Heavy sleet showers and thunder",
+        "lightsnowshowers" => "Light snow showers",
+        "snowshowers" => "Snow showers",
+        "heavysnowshowers" => "Heavy snow showers",
+        "lightssnowshowersandthunder" => "Light snow showers and thunder",
+        "snowshowersandthunder" => "Snow showers and thunder",
+        "heavysnowshowersandthunder" => "Heavy snow showers and thunder",
+        "lightrain" => "Light rain",
+        "rain" => "Rain",
+        "heavyrain" => "Heavy rain",
+        "lightrainandthunder" => "Light rain and thunder",
+        "rainandthunder" => "Rain and thunder",
+        "heavyrainandthunder" => "Heavy rain and thunder",
+        "lightsleet" => "Light sleet",
+        "sleet" => "Sleet",
+        "heavysleet" => "Heavy sleet",
+        "lightsleetandthunder" => "Light sleet and thunder",
+        "sleetandthunder" => "Sleet and thunder",
+        "heavysleetandthunder" => "Heavy sleet and thunder",
+        "lightsnow" => "Light snow",
+        "snow" => "Snow",
+        "heavysnow" => "Heavy snow",
+        "lightsnowandthunder" => "Light snow and thunder",
+        "snowandthunder" => "Snow and thunder",
+        "heavysnowandthunder" => "Heavy snow and thunder",
+        _ => return None,
+    };
+    Some(description.to_string())
+}
+
+impl Provider for MetNorway {
+    /// Current conditions are the forecast for the current hour, as no observations are provided.
+    fn get_weather(&self, location: &Location, date: Option<DateTime<Utc>>) -> Result<WeatherInfo> {
+        let date = forecast_date(date, Duration::days(FORECAST_HORIZON_DAYS.into()))?
+            .unwrap_or_else(Utc::now);
+
+        match closest_entry(self.get_entries(location)?, date) {
+            Some(entry) => Ok(entry.weather),
+            None => Err(WeatherError::Parse("No forecast received".to_string())),
+        }
+    }
+
+    fn get_forecast(&self, location: &Location, days: u32, hourly: bool) -> Result<Forecast> {
+        check_forecast_days(days, FORECAST_HORIZON_DAYS)?;
+
+        let now = Utc::now();
+        let until = now + Duration::days(days.into());
+        let entries = self.get_entries(location)?;
+
+        let entries = if hourly {
+            // Hours passed are of no interest.
+            entries
+                .into_iter()
+                .filter(|e| e.time >= now - Duration::hours(1) && e.time < until)
+                .collect()
+        } else {
+            daily_entries(entries)
+                .into_iter()
+                .filter(|e| e.time < until)
+                .collect()
+        };
+
+        Ok(Forecast { entries })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Cache storage holding the same unexpired value under any key.
+    struct StoredCacheStorage(Option<serde_json::Value>);
+
+    impl CacheStorage for StoredCacheStorage {
+        fn get(&self, _key: &str) -> Result<Option<serde_json::Value>> {
+            Ok(self.0.clone())
+        }
+
+        fn get_last(&self, _key: &str) -> Result<Option<crate::cache::CachedValue>> {
+            Ok(self.0.clone().map(|value| crate::cache::CachedValue {
+                value,
+                stored_at: Utc::now(),
+            }))
+        }
+
+        fn put(&self, _key: &str, _value: &serde_json::Value, _ttl: Duration) -> Result<()> {
+            Ok(())
+        }
+
+        fn remove(&self, _key: &str) -> Result<()> {
+            Ok(())
+        }
+
+        fn stats(&self) -> Result<crate::cache::CacheStats> {
+            Ok(crate::cache::CacheStats::default())
+        }
+
+        fn clear(&self) -> Result<usize> {
+            Ok(0)
+        }
+
+        fn clear_scope(&self, _scope: &str) -> Result<usize> {
+            Ok(0)
+        }
+    }
+
+    #[test]
+    fn stored_forecast_as_cache_mode_allows() {
+        let location = Location {
+            latitude: 59.91,
+            longitude: 10.75,
+            ..Location::default()
+        };
+        let stored = serde_json::json!({
+            "last_modified": "Tue, 14 Feb 2023 12:00:00 GMT",
+            "data": { "properties": { "timeseries": [] } },
+        });
+
+        // No contact is configured, so any request would fail with a config error.
+        let provider = |mode| {
+            MetNorway::new(
+                None,
+                Box::new(StoredCacheStorage(Some(stored.clone()))),
+                mode,
+            )
+        };
+        assert_eq!(
+            stored["data"],
+            provider(CacheMode::Use)
+                .get_forecast_data(&location)
+                .unwrap()
+        );
+        for mode in [CacheMode::Refresh, CacheMode::Bypass] {
+            assert_eq!(
+                Some(3),
+                provider(mode)
+                    .get_forecast_data(&location)
+                    .err()
+                    .map(|e| e.exit_code())
+            );
+        }
+    }
+
+    #[test]
+    fn user_agent_with_contact() {
+        let provider = MetNorway::new(
+            Some("you@example.com".to_string()),
+            Box::new(StoredCacheStorage(None)),
+            CacheMode::Use,
+        );
+        assert_eq!(
+            format!("{USER_AGENT} (you@example.com)"),
+            provider.user_agent().unwrap()
+        );
+
+        let provider = MetNorway::new(None, Box::new(StoredCacheStorage(None)), CacheMode::Use);
+        assert_eq!(Some(3), provider.user_agent().err().map(|e| e.exit_code()));
+    }
+
+    #[test]
+    fn truncate_coordinates() {
+        assert_eq!("50.4501", truncate(50.450_199_9));
+        assert_eq!("-33.8688", truncate(-33.868_82));
+        assert_eq!("30.0000", truncate(30.0));
+    }
+
+    #[test]
+    fn describe_symbol_codes() {
+        assert_eq!(
+            Some("Light rain showers".to_string()),
+            describe_symbol_code("lightrainshowers_day")
+        );
+        assert_eq!(Some("Cloudy".to_string()), describe_symbol_code("cloudy"));
+        assert_eq!(
+            Some("Light sleet showers and thunder".to_string()),
+            describe_symbol_code("lightssleetshowersandthunder_polartwilight")
+        );
+        assert_eq!(None, describe_symbol_code("unknown_night"));
+    }
+
+    #[test]
+    fn revalidate_stored_forecast() {
+        assert!(conditional_headers(None).is_empty());
+        assert!(conditional_headers(Some(&serde_json::json!({ "data": {} }))).is_empty());
+
+        let stored = serde_json::json!({
+            "last_modified": "Tue, 14 Feb 2023 12:00:00 GMT",
+            "data": {},
+        });
+        assert_eq!(
+            "Tue, 14 Feb 2023 12:00:00 GMT",
+            conditional_headers(Some(&stored))[IF_MODIFIED_SINCE]
+        );
+    }
+
+    #[test]
+    fn time_to_live_until_expires() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(Duration::zero(), time_to_live(&headers));
+
+        let expires = Utc::now() + Duration::minutes(30);
+        headers.insert(
+            EXPIRES,
+            HeaderValue::from_str(&expires.to_rfc2822()).unwrap(),
+        );
+        let ttl = time_to_live(&headers);
+        assert!(ttl > Duration::minutes(29) && ttl <= Duration::minutes(30));
+
+        headers.insert(
+            EXPIRES,
+            HeaderValue::from_static("Tue, 14 Feb 2023 12:00:00 GMT"),
+        );
+        assert_eq!(Duration::zero(), time_to_live(&headers));
+
+        headers.insert(EXPIRES, HeaderValue::from_static("0"));
+        assert_eq!(Duration::zero(), time_to_live(&headers));
+    }
+}
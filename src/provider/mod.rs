@@ -1,59 +1,576 @@
+mod fallback_provider;
 mod production_provider_factory;
 mod provider_factory;
 
 mod accu_weather;
+mod met_norway;
+mod nws;
+mod open_meteo;
 mod open_weather;
+mod weather_api;
 
-use std::error::Error;
+pub mod units;
+
+use chrono::{DateTime, Duration, NaiveDate, SecondsFormat, TimeZone, Timelike, Utc};
+use reqwest::blocking::Response;
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use serde::ser::{SerializeMap, Serializer};
+use serde::Serialize;
+use std::collections::BTreeMap;
 use std::fmt;
 
+pub use fallback_provider::FallbackProvider;
 pub use production_provider_factory::ProductionProviderFactory;
 pub use provider_factory::ProviderFactory;
 
+use crate::error::{Result, WeatherError};
+use crate::geocoder::Location;
+
+use units::{Length, Pressure, Quantity, Speed, Temperature, Unit, Units};
+
 /// Weather condition info.
 ///
 /// All fields MUST be optional. Provider is allowed to fill in available info.
+#[derive(Default)]
 pub struct WeatherInfo {
     /// Textual weather description
     pub description: Option<String>,
 
-    /// Temperature in Celsius
-    pub temperature: Option<f64>,
+    /// Temperature
+    pub temperature: Option<Temperature>,
 
     /// Humidity in percent
     pub humidity: Option<i64>,
 
-    /// Pressure in Pascal
-    pub pressure: Option<i64>,
+    /// Atmospheric pressure
+    pub pressure: Option<Pressure>,
+
+    /// Feels-like temperature
+    pub feels_like: Option<Temperature>,
+
+    /// Dew point
+    pub dew_point: Option<Temperature>,
+
+    /// Wind speed
+    pub wind_speed: Option<Speed>,
+
+    /// Wind direction in degrees (meteorological, 0 is north)
+    pub wind_direction: Option<i64>,
+
+    /// Wind gusts speed
+    pub wind_gust: Option<Speed>,
+
+    /// Precipitation amount
+    pub precipitation: Option<Length>,
+
+    /// Precipitation probability in percent
+    pub precipitation_probability: Option<i64>,
+
+    /// Cloud cover in percent
+    pub cloud_cover: Option<i64>,
+
+    /// Visibility
+    pub visibility: Option<Length>,
+
+    /// UV index
+    pub uv_index: Option<f64>,
+
+    /// Sunrise time
+    pub sunrise: Option<DateTime<Utc>>,
+
+    /// Sunset time
+    pub sunset: Option<DateTime<Utc>>,
+
+    /// US EPA air quality index, from 1 (good) to 6 (hazardous)
+    pub air_quality: Option<i64>,
+}
+
+impl WeatherInfo {
+    /// Makes displayable weather info with values in the specified units.
+    pub fn display(&self, units: Units) -> WeatherInfoDisplay<'_> {
+        WeatherInfoDisplay { info: self, units }
+    }
 }
 
 impl fmt::Display for WeatherInfo {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.display(Units::default()).fmt(f)
+    }
+}
+
+/// Weather info displayed in the specific units.
+pub struct WeatherInfoDisplay<'a> {
+    info: &'a WeatherInfo,
+    units: Units,
+}
+
+impl fmt::Display for WeatherInfoDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let info = self.info;
+        let units = self.units;
+
         write!(
             f,
             "{}\n-> Temperature: {}\n-> Humidity: {}\n-> Pressure: {}",
-            self.description
+            info.description
                 .as_ref()
                 .unwrap_or(&String::from("unknown weather description")),
-            self.temperature
-                .map(|e| e.to_string())
+            info.temperature
+                .map(|e| e.format(units.temperature))
                 .unwrap_or_else(|| "unknown".to_string()),
-            self.humidity
-                .map(|e| e.to_string())
+            info.humidity
+                .map(|e| format!("{e}%"))
                 .unwrap_or_else(|| "unknown".to_string()),
-            self.pressure
-                .map(|e| e.to_string())
+            info.pressure
+                .map(|e| e.format(units.pressure))
                 .unwrap_or_else(|| "unknown".to_string()),
-        )
+        )?;
+
+        // Extended info is shown only if provided.
+        if let Some(feels_like) = info.feels_like {
+            write!(
+                f,
+                "\n-> Feels like: {}",
+                feels_like.format(units.temperature)
+            )?;
+        }
+        if let Some(dew_point) = info.dew_point {
+            write!(f, "\n-> Dew point: {}", dew_point.format(units.temperature))?;
+        }
+        if let Some(wind_speed) = info.wind_speed {
+            write!(f, "\n-> Wind: {}", wind_speed.format(units.speed))?;
+            if let Some(wind_direction) = info.wind_direction {
+                write!(f, " from {wind_direction}°")?;
+            }
+            if let Some(wind_gust) = info.wind_gust {
+                write!(f, ", gusts {}", wind_gust.format(units.speed))?;
+            }
+        }
+        if let Some(precipitation) = info.precipitation {
+            write!(
+                f,
+                "\n-> Precipitation: {}",
+                precipitation.format(units.precipitation)
+            )?;
+        }
+        if let Some(precipitation_probability) = info.precipitation_probability {
+            write!(
+                f,
+                "\n-> Precipitation probability: {precipitation_probability}%"
+            )?;
+        }
+        if let Some(cloud_cover) = info.cloud_cover {
+            write!(f, "\n-> Cloud cover: {cloud_cover}%")?;
+        }
+        if let Some(visibility) = info.visibility {
+            write!(f, "\n-> Visibility: {}", visibility.format(units.distance))?;
+        }
+        if let Some(uv_index) = info.uv_index {
+            write!(f, "\n-> UV index: {uv_index}")?;
+        }
+        if let Some(sunrise) = info.sunrise {
+            write!(f, "\n-> Sunrise: {}", sunrise.format("%H:%M UTC"))?;
+        }
+        if let Some(sunset) = info.sunset {
+            write!(f, "\n-> Sunset: {}", sunset.format("%H:%M UTC"))?;
+        }
+        if let Some(air_quality) = info.air_quality {
+            write!(
+                f,
+                "\n-> Air quality: {} (US EPA index {air_quality})",
+                describe_air_quality(air_quality)
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Describes US EPA air quality index.
+fn describe_air_quality(index: i64) -> &'static str {
+    match index {
+        i64::MIN..=1 => "Good",
+        2 => "Moderate",
+        3 => "Unhealthy for sensitive groups",
+        4 => "Unhealthy",
+        5 => "Very unhealthy",
+        _ => "Hazardous",
+    }
+}
+
+impl WeatherInfoDisplay<'_> {
+    /// Names of the weather info fields in the order they are returned by `fields`.
+    pub const FIELD_NAMES: [&'static str; 17] = [
+        "description",
+        "temperature",
+        "feels_like",
+        "dew_point",
+        "humidity",
+        "pressure",
+        "wind_speed",
+        "wind_direction",
+        "wind_gust",
+        "precipitation",
+        "precipitation_probability",
+        "cloud_cover",
+        "visibility",
+        "uv_index",
+        "sunrise",
+        "sunset",
+        "air_quality",
+    ];
+
+    /// Gets all fields with values in the display units, in the stable order.
+    pub fn fields(&self) -> Vec<Field> {
+        let info = self.info;
+        let units = self.units;
+
+        let temperature =
+            |e: Option<Temperature>| e.map(|e| FieldValue::Number(e.rounded(units.temperature)));
+        let speed = |e: Option<Speed>| e.map(|e| FieldValue::Number(e.rounded(units.speed)));
+        let integer = |e: Option<i64>| e.map(FieldValue::Integer);
+        let time = |e: Option<DateTime<Utc>>| e.map(FieldValue::Time);
+
+        let values = [
+            (info.description.clone().map(FieldValue::Text), None),
+            (
+                temperature(info.temperature),
+                Some(units.temperature.symbol()),
+            ),
+            (
+                temperature(info.feels_like),
+                Some(units.temperature.symbol()),
+            ),
+            (
+                temperature(info.dew_point),
+                Some(units.temperature.symbol()),
+            ),
+            (integer(info.humidity), Some("%")),
+            (
+                info.pressure
+                    .map(|e| FieldValue::Number(e.rounded(units.pressure))),
+                Some(units.pressure.symbol()),
+            ),
+            (speed(info.wind_speed), Some(units.speed.symbol())),
+            (integer(info.wind_direction), Some("°")),
+            (speed(info.wind_gust), Some(units.speed.symbol())),
+            (
+                info.precipitation
+                    .map(|e| FieldValue::Number(e.rounded(units.precipitation))),
+                Some(units.precipitation.symbol()),
+            ),
+            (integer(info.precipitation_probability), Some("%")),
+            (integer(info.cloud_cover), Some("%")),
+            (
+                info.visibility
+                    .map(|e| FieldValue::Number(e.rounded(units.distance))),
+                Some(units.distance.symbol()),
+            ),
+            (info.uv_index.map(FieldValue::Number), None),
+            (time(info.sunrise), None),
+            (time(info.sunset), None),
+            (integer(info.air_quality), None),
+        ];
+
+        Self::FIELD_NAMES
+            .iter()
+            .zip(values)
+            .map(|(name, (value, unit))| Field { name, value, unit })
+            .collect()
+    }
+}
+
+impl Serialize for WeatherInfoDisplay<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let fields = self.fields();
+        let mut map = serializer.serialize_map(Some(fields.len()))?;
+        for field in fields {
+            map.serialize_entry(field.name, &field.value)?;
+        }
+        map.end()
+    }
+}
+
+/// Serialized as a flat map of fields with values in the default units.
+impl Serialize for WeatherInfo {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.display(Units::default()).serialize(serializer)
+    }
+}
+
+/// Weather info field.
+pub struct Field {
+    pub name: &'static str,
+
+    /// Value in the display units, if provided
+    pub value: Option<FieldValue>,
+
+    /// Display unit symbol, if applicable
+    pub unit: Option<&'static str>,
+}
+
+/// Weather info field value.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FieldValue {
+    Text(String),
+    Number(f64),
+    Integer(i64),
+    Time(DateTime<Utc>),
+}
+
+impl fmt::Display for FieldValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldValue::Text(value) => value.fmt(f),
+            FieldValue::Number(value) => value.fmt(f),
+            FieldValue::Integer(value) => value.fmt(f),
+            FieldValue::Time(value) => {
+                write!(f, "{}", value.to_rfc3339_opts(SecondsFormat::Secs, true))
+            }
+        }
+    }
+}
+
+/// Serialized as a plain value, time is serialized as RFC 3339 string.
+impl Serialize for FieldValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            FieldValue::Text(value) => serializer.serialize_str(value),
+            FieldValue::Number(value) => serializer.serialize_f64(*value),
+            FieldValue::Integer(value) => serializer.serialize_i64(*value),
+            FieldValue::Time(value) => {
+                serializer.serialize_str(&value.to_rfc3339_opts(SecondsFormat::Secs, true))
+            }
+        }
+    }
+}
+
+/// Weather forecast entry.
+pub struct ForecastEntry {
+    /// Time the entry refers to
+    pub time: DateTime<Utc>,
+
+    /// Forecasted weather conditions
+    pub weather: WeatherInfo,
+}
+
+/// Weather forecast.
+///
+/// Time series of forecast entries ordered by time.
+pub struct Forecast {
+    pub entries: Vec<ForecastEntry>,
+}
+
+impl Forecast {
+    /// Makes displayable forecast with values in the specified units.
+    pub fn display(&self, units: Units) -> ForecastDisplay<'_> {
+        ForecastDisplay {
+            forecast: self,
+            units,
+        }
+    }
+}
+
+impl fmt::Display for Forecast {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.display(Units::default()).fmt(f)
+    }
+}
+
+/// Forecast displayed in the specific units.
+pub struct ForecastDisplay<'a> {
+    forecast: &'a Forecast,
+    units: Units,
+}
+
+impl fmt::Display for ForecastDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, entry) in self.forecast.entries.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            writeln!(f, "[{}]", entry.time.format("%Y-%m-%d %H:%M UTC"))?;
+            writeln!(f, "{}", entry.weather.display(self.units))?;
+        }
+        Ok(())
     }
 }
 
 /// Weather provider
+///
+/// Accepts locations resolved by a `Geocoder`.
 pub trait Provider {
-    /// Performs weather condition discovery
-    fn get_weather(
-        &self,
-        address: String,
-        date: Option<String>,
-    ) -> Result<WeatherInfo, Box<dyn Error>>;
+    /// Performs weather condition discovery.
+    ///
+    /// Current conditions are returned if `date` is not specified,
+    /// otherwise the forecast for the specified date.
+    fn get_weather(&self, location: &Location, date: Option<DateTime<Utc>>) -> Result<WeatherInfo>;
+
+    /// Performs weather forecast discovery.
+    ///
+    /// Returns the forecast for the specified number of days starting from now,
+    /// either hourly (or the finest interval the provider supports) or daily.
+    fn get_forecast(&self, location: &Location, days: u32, hourly: bool) -> Result<Forecast>;
+
+    /// Performs historical weather discovery.
+    ///
+    /// Returns observed conditions for the specified past date.
+    /// Fails as unsupported unless overridden by the provider.
+    fn get_history(&self, _location: &Location, _date: DateTime<Utc>) -> Result<WeatherInfo> {
+        Err(history_not_supported(None))
+    }
+
+    /// Gets the name of the provider answered the last request.
+    ///
+    /// Known to providers delegating requests to named ones only.
+    fn answered(&self) -> Option<String> {
+        None
+    }
+}
+
+impl<P: Provider + ?Sized> Provider for Box<P> {
+    fn get_weather(&self, location: &Location, date: Option<DateTime<Utc>>) -> Result<WeatherInfo> {
+        (**self).get_weather(location, date)
+    }
+
+    fn get_forecast(&self, location: &Location, days: u32, hourly: bool) -> Result<Forecast> {
+        (**self).get_forecast(location, days, hourly)
+    }
+
+    fn get_history(&self, location: &Location, date: DateTime<Utc>) -> Result<WeatherInfo> {
+        (**self).get_history(location, date)
+    }
+
+    fn answered(&self) -> Option<String> {
+        (**self).answered()
+    }
+}
+
+/// Makes error telling that historical weather is not available from the provider
+/// for the requested date, optionally the oldest date it is available since.
+pub(crate) fn history_not_supported(since: Option<DateTime<Utc>>) -> WeatherError {
+    WeatherError::Unsupported(match since {
+        Some(since) => format!(
+            "History is only available since {} for this provider",
+            since.format("%Y-%m-%d %H:%M UTC")
+        ),
+        None => "History is not supported by this provider".to_string(),
+    })
+}
+
+/// Requested dates closer to now than this many minutes refer to the current conditions.
+const CURRENT_DATE_TOLERANCE_MINUTES: i64 = 60;
+
+/// Checks whether the date refers to the past rather than to the current conditions.
+pub fn is_past(date: DateTime<Utc>) -> bool {
+    date < Utc::now() - Duration::minutes(CURRENT_DATE_TOLERANCE_MINUTES)
+}
+
+/// Checks the requested date against the provider forecast horizon.
+///
+/// Returns `None` if current conditions are requested.
+fn forecast_date(date: Option<DateTime<Utc>>, horizon: Duration) -> Result<Option<DateTime<Utc>>> {
+    let date = match date {
+        Some(date) => date,
+        None => return Ok(None),
+    };
+
+    let now = Utc::now();
+    let tolerance = Duration::minutes(CURRENT_DATE_TOLERANCE_MINUTES);
+
+    if is_past(date) {
+        return Err(WeatherError::Unsupported(format!(
+            "Date {} is in the past, use historical lookup instead of forecast",
+            date.format("%Y-%m-%d %H:%M UTC")
+        )));
+    }
+    if date <= now + tolerance {
+        return Ok(None);
+    }
+    if date > now + horizon {
+        return Err(WeatherError::Unsupported(format!(
+            "Date {} is beyond the provider forecast horizon ({} days)",
+            date.format("%Y-%m-%d %H:%M UTC"),
+            horizon.num_days()
+        )));
+    }
+
+    Ok(Some(date))
+}
+
+/// Checks the requested number of forecast days against the provider forecast horizon.
+fn check_forecast_days(days: u32, horizon_days: u32) -> Result<()> {
+    if days == 0 {
+        return Err(WeatherError::Unsupported(
+            "Number of forecast days must be positive".to_string(),
+        ));
+    }
+    if days > horizon_days {
+        return Err(WeatherError::Unsupported(format!(
+            "{days} days is beyond the provider forecast horizon ({horizon_days} days)"
+        )));
+    }
+    Ok(())
+}
+
+/// Converts Unix timestamp to UTC time.
+fn from_timestamp(timestamp: Option<i64>) -> Option<DateTime<Utc>> {
+    Utc.timestamp_opt(timestamp?, 0).single()
+}
+
+/// Picks the entry closest to the date.
+fn closest_entry(entries: Vec<ForecastEntry>, date: DateTime<Utc>) -> Option<ForecastEntry> {
+    entries
+        .into_iter()
+        .min_by_key(|e| (e.time - date).num_seconds().abs())
+}
+
+/// Reduces entries of a few hours to a single entry per day, the one closest to midday (UTC).
+fn daily_entries(entries: Vec<ForecastEntry>) -> Vec<ForecastEntry> {
+    let mut days: BTreeMap<NaiveDate, ForecastEntry> = BTreeMap::new();
+
+    let distance_to_midday = |time: &DateTime<Utc>| (time.hour() as i32 - 12).abs();
+
+    for entry in entries {
+        match days.get(&entry.time.date_naive()) {
+            Some(existing)
+                if distance_to_midday(&existing.time) <= distance_to_midday(&entry.time) => {}
+            _ => {
+                days.insert(entry.time.date_naive(), entry);
+            }
+        }
+    }
+
+    days.into_values().collect()
+}
+
+/// Identifies the utility to the providers, some of them reject anonymous requests.
+pub(crate) static USER_AGENT: &str =
+    concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+/// Performs GET request with the additional headers, leaving the response status unchecked.
+pub(crate) fn send_get(url: &str, headers: HeaderMap) -> Result<Response> {
+    reqwest::blocking::Client::builder()
+        .user_agent(USER_AGENT)
+        .build()
+        .and_then(|client| client.get(url).headers(headers).send())
+        .map_err(|e| WeatherError::Network(format!("Request failed ({e})")))
+}
+
+/// Parses JSON response data.
+pub(crate) fn parse_json(response: Response) -> Result<serde_json::Value> {
+    response
+        .json::<serde_json::Value>()
+        .map_err(|e| WeatherError::Parse(format!("Failed to parse response data ({e})")))
+}
+
+/// Performs GET request and parses JSON response.
+pub(crate) fn get_json(url: &str) -> Result<serde_json::Value> {
+    let response = send_get(url, HeaderMap::new())?;
+    if response.status() != StatusCode::OK {
+        return Err(WeatherError::from_status(response.status()));
+    }
+
+    parse_json(response)
 }
@@ -0,0 +1,444 @@
+use crate::provider::{
+    check_forecast_days, closest_entry, forecast_date, get_json, history_not_supported, Forecast,
+    ForecastEntry, Provider, WeatherInfo,
+};
+
+use crate::error::{Result, WeatherError};
+use crate::geocoder::Location;
+use crate::provider::units::{Length, Pressure, Speed, Temperature};
+
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+
+static POINTS_API_URL: &str = "https://api.weather.gov/points";
+static STATIONS_API_URL: &str = "https://api.weather.gov/stations";
+
+/// Forecast horizon of the gridpoint forecast.
+const FORECAST_HORIZON_DAYS: u32 = 7;
+
+/// Station observations are kept for about this many past days.
+const HISTORY_HORIZON_DAYS: i64 = 7;
+
+/// Hourly forecast is preferred for dates it covers within this tolerance,
+/// the coarser forecast periods are used otherwise.
+const HOURLY_TOLERANCE_MINUTES: i64 = 60;
+
+/// US National Weather Service API, available for US locations only.
+///
+/// Forecasts are issued by forecast offices on a grid, so the gridpoint endpoints are looked up
+/// for the location first. Current and historical conditions are observed by the nearest station.
+pub struct Nws;
+
+/// Endpoints of the gridpoint covering the location.
+struct Gridpoint {
+    /// Forecast of 12-hour day and night periods
+    forecast: String,
+
+    /// Hourly forecast
+    forecast_hourly: String,
+
+    /// Observation stations ordered by distance
+    observation_stations: String,
+}
+
+impl Nws {
+    fn get_gridpoint(&self, location: &Location) -> Result<Gridpoint> {
+        // Coordinates of higher precision are redirected.
+        let data = get_json(&format!(
+            "{}/{:.4},{:.4}",
+            POINTS_API_URL, location.latitude, location.longitude,
+        ))
+        .map_err(|e| match e {
+            WeatherError::NotFound(_) => {
+                e.context("Location is not covered by the National Weather Service")
+            }
+            e => e,
+        })?;
+
+        let properties = &data["properties"];
+        let endpoint = |name: &str| {
+            properties[name]
+                .as_str()
+                .map(|e| e.to_string())
+                .ok_or_else(|| WeatherError::Parse("No gridpoint received".to_string()))
+        };
+
+        Ok(Gridpoint {
+            forecast: endpoint("forecast")?,
+            forecast_hourly: endpoint("forecastHourly")?,
+            observation_stations: endpoint("observationStations")?,
+        })
+    }
+
+    /// Gets identifier of the observation station nearest to the location.
+    fn get_station(&self, location: &Location) -> Result<String> {
+        let data = get_json(&self.get_gridpoint(location)?.observation_stations)?;
+
+        match data["features"][0]["properties"]["stationIdentifier"].as_str() {
+            Some(station) => Ok(station.to_string()),
+            None => Err(WeatherError::NotFound(
+                "No observation station found".to_string(),
+            )),
+        }
+    }
+
+    fn get_current(&self, location: &Location) -> Result<WeatherInfo> {
+        let data = get_json(&format!(
+            "{}/{}/observations/latest",
+            STATIONS_API_URL,
+            self.get_station(location)?,
+        ))?;
+
+        let properties = &data["properties"];
+        if !properties.is_object() {
+            return Err(WeatherError::Parse(
+                "No weather condition received".to_string(),
+            ));
+        }
+
+        Ok(parse_observation(properties))
+    }
+
+    /// Gets forecast periods from the gridpoint forecast endpoint.
+    fn get_periods(&self, url: &str) -> Result<Vec<serde_json::Value>> {
+        let mut data = get_json(url)?;
+
+        match data["properties"]["periods"].take() {
+            serde_json::Value::Array(periods) if !periods.is_empty() => Ok(periods),
+            _ => Err(WeatherError::Parse("No forecast received".to_string())),
+        }
+    }
+}
+
+/// Parses forecast period start time, given with the local time offset.
+fn parse_time(time: &serde_json::Value) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(time.as_str()?)
+        .ok()
+        .map(|e| e.with_timezone(&Utc))
+}
+
+/// Parses temperature measurement, a value with WMO unit code.
+fn temperature(data: &serde_json::Value) -> Option<Temperature> {
+    let value = data["value"].as_f64()?;
+    match data["unitCode"].as_str()? {
+        "wmoUnit:degC" => Some(Temperature::from_celsius(value)),
+        "wmoUnit:degF" => Some(Temperature::from_fahrenheit(value)),
+        "wmoUnit:K" => Some(Temperature::from_kelvin(value)),
+        _ => None,
+    }
+}
+
+/// Parses speed measurement, a value with WMO unit code.
+fn speed(data: &serde_json::Value) -> Option<Speed> {
+    let value = data["value"].as_f64()?;
+    match data["unitCode"].as_str()? {
+        "wmoUnit:km_h-1" => Some(Speed::from_kilometers_per_hour(value)),
+        "wmoUnit:m_s-1" => Some(Speed::from_meters_per_second(value)),
+        "wmoUnit:kt" => Some(Speed::from_knots(value)),
+        _ => None,
+    }
+}
+
+/// Parses pressure measurement, a value with WMO unit code.
+fn pressure(data: &serde_json::Value) -> Option<Pressure> {
+    let value = data["value"].as_f64()?;
+    match data["unitCode"].as_str()? {
+        "wmoUnit:Pa" => Some(Pressure::from_pascals(value)),
+        "wmoUnit:hPa" => Some(Pressure::from_hectopascals(value)),
+        _ => None,
+    }
+}
+
+/// Parses length measurement, a value with WMO unit code.
+fn length(data: &serde_json::Value) -> Option<Length> {
+    let value = data["value"].as_f64()?;
+    match data["unitCode"].as_str()? {
+        "wmoUnit:mm" => Some(Length::from_millimeters(value)),
+        "wmoUnit:m" => Some(Length::from_meters(value)),
+        "wmoUnit:km" => Some(Length::from_kilometers(value)),
+        _ => None,
+    }
+}
+
+/// Parses percent or degrees measurement.
+fn integer(data: &serde_json::Value) -> Option<i64> {
+    data["value"].as_f64().map(|e| e.round() as i64)
+}
+
+/// Converts METAR cloud layer amount to cloud cover in percent.
+fn cloud_amount_percent(amount: &str) -> Option<i64> {
+    match amount {
+        "SKC" | "CLR" => Some(0),
+        "FEW" => Some(19),
+        "SCT" => Some(44),
+        "BKN" => Some(75),
+        "OVC" | "VV" => Some(100),
+        _ => None,
+    }
+}
+
+/// Parses station observation.
+fn parse_observation(observation: &serde_json::Value) -> WeatherInfo {
+    WeatherInfo {
+        description: observation["textDescription"]
+            .as_str()
+            .filter(|e| !e.is_empty())
+            .map(|e| e.to_string()),
+        temperature: temperature(&observation["temperature"]),
+        humidity: integer(&observation["relativeHumidity"]),
+        pressure: pressure(&observation["seaLevelPressure"])
+            .or_else(|| pressure(&observation["barometricPressure"])),
+        feels_like: temperature(&observation["heatIndex"])
+            .or_else(|| temperature(&observation["windChill"])),
+        dew_point: temperature(&observation["dewpoint"]),
+        wind_speed: speed(&observation["windSpeed"]),
+        wind_direction: integer(&observation["windDirection"]),
+        wind_gust: speed(&observation["windGust"]),
+        precipitation: length(&observation["precipitationLastHour"]),
+        precipitation_probability: None,
+        // Sky is as covered as its densest layer.
+        cloud_cover: observation["cloudLayers"].as_array().and_then(|layers| {
+            layers
+                .iter()
+                .filter_map(|e| cloud_amount_percent(e["amount"].as_str()?))
+                .max()
+        }),
+        visibility: length(&observation["visibility"]),
+        uv_index: None,
+        sunrise: None,
+        sunset: None,
+        air_quality: None,
+    }
+}
+
+/// Parses forecast wind speed given as text like "10 mph" or "5 to 10 mph",
+/// the upper bound of a range is taken.
+fn parse_wind_speed(data: &serde_json::Value) -> Option<Speed> {
+    if data.is_object() {
+        return speed(data);
+    }
+
+    let mut words = data.as_str()?.split_whitespace().rev();
+    let unit = words.next()?;
+    let value = words.next()?.parse::<f64>().ok()?;
+    match unit {
+        "mph" => Some(Speed::from_miles_per_hour(value)),
+        "km/h" => Some(Speed::from_kilometers_per_hour(value)),
+        _ => None,
+    }
+}
+
+/// Converts 16-point compass direction to degrees.
+fn compass_degrees(direction: &str) -> Option<i64> {
+    const POINTS: [&str; 16] = [
+        "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE", "S", "SSW", "SW", "WSW", "W", "WNW",
+        "NW", "NNW",
+    ];
+    let index = POINTS.iter().position(|e| *e == direction)?;
+    Some((index as f64 * 22.5).round() as i64)
+}
+
+/// Parses forecast period, both hourly and 12-hour ones.
+fn parse_period(period: &serde_json::Value) -> Option<ForecastEntry> {
+    // Temperature is given either as a plain value or as a measurement.
+    let period_temperature = match (
+        period["temperature"].as_f64(),
+        period["temperatureUnit"].as_str(),
+    ) {
+        (Some(value), Some("F")) => Some(Temperature::from_fahrenheit(value)),
+        (Some(value), Some("C")) => Some(Temperature::from_celsius(value)),
+        _ => temperature(&period["temperature"]),
+    };
+
+    Some(ForecastEntry {
+        time: parse_time(&period["startTime"])?,
+        weather: WeatherInfo {
+            description: period["shortForecast"].as_str().map(|e| e.to_string()),
+            temperature: period_temperature,
+            humidity: integer(&period["relativeHumidity"]),
+            dew_point: temperature(&period["dewpoint"]),
+            wind_speed: parse_wind_speed(&period["windSpeed"]),
+            wind_direction: period["windDirection"].as_str().and_then(compass_degrees),
+            wind_gust: parse_wind_speed(&period["windGust"]),
+            precipitation_probability: integer(&period["probabilityOfPrecipitation"]),
+            ..WeatherInfo::default()
+        },
+    })
+}
+
+/// Picks a period per day out of the day and night ones, the daytime one if available.
+fn daily_periods(periods: &[serde_json::Value]) -> Vec<&serde_json::Value> {
+    // Days are told apart by the local date of the forecast office.
+    let local_date = |period: &serde_json::Value| {
+        period["startTime"]
+            .as_str()
+            .and_then(|e| DateTime::parse_from_rfc3339(e).ok())
+            .map(|e| e.date_naive())
+    };
+    let is_daytime = |period: &serde_json::Value| period["isDaytime"].as_bool() == Some(true);
+
+    let mut days: Vec<&serde_json::Value> = Vec::new();
+    for period in periods {
+        match days.last_mut() {
+            Some(last) if local_date(last) == local_date(period) => {
+                if is_daytime(period) && !is_daytime(last) {
+                    *last = period;
+                }
+            }
+            _ => days.push(period),
+        }
+    }
+    days
+}
+
+impl Provider for Nws {
+    fn get_weather(&self, location: &Location, date: Option<DateTime<Utc>>) -> Result<WeatherInfo> {
+        let date = match forecast_date(date, Duration::days(FORECAST_HORIZON_DAYS.into()))? {
+            Some(date) => date,
+            None => return self.get_current(location),
+        };
+
+        let gridpoint = self.get_gridpoint(location)?;
+        let hourly = self.get_periods(&gridpoint.forecast_hourly)?;
+        let entry = closest_entry(hourly.iter().filter_map(parse_period).collect(), date)
+            .filter(|e| (e.time - date).num_minutes().abs() <= HOURLY_TOLERANCE_MINUTES);
+
+        // Hourly forecast is shorter than the forecast of periods.
+        let entry = match entry {
+            Some(entry) => Some(entry),
+            None => {
+                let periods = self.get_periods(&gridpoint.forecast)?;
+                closest_entry(periods.iter().filter_map(parse_period).collect(), date)
+            }
+        };
+
+        match entry {
+            Some(entry) => Ok(entry.weather),
+            None => Err(WeatherError::Parse("No forecast received".to_string())),
+        }
+    }
+
+    fn get_forecast(&self, location: &Location, days: u32, hourly: bool) -> Result<Forecast> {
+        check_forecast_days(days, FORECAST_HORIZON_DAYS)?;
+
+        let gridpoint = self.get_gridpoint(location)?;
+        let until = Utc::now() + Duration::days(days.into());
+
+        let entries = if hourly {
+            // Hours passed are of no interest.
+            let since = Utc::now() - Duration::hours(1);
+            self.get_periods(&gridpoint.forecast_hourly)?
+                .iter()
+                .filter_map(parse_period)
+                .filter(|e| e.time >= since && e.time < until)
+                .collect()
+        } else {
+            daily_periods(&self.get_periods(&gridpoint.forecast)?)
+                .into_iter()
+                .filter_map(parse_period)
+                .filter(|e| e.time < until)
+                .collect()
+        };
+
+        Ok(Forecast { entries })
+    }
+
+    /// Observations of the nearest station are available for the last week only.
+    fn get_history(&self, location: &Location, date: DateTime<Utc>) -> Result<WeatherInfo> {
+        let since = Utc::now() - Duration::days(HISTORY_HORIZON_DAYS);
+        if date < since {
+            return Err(history_not_supported(Some(since)));
+        }
+
+        let window = Duration::hours(1);
+        let data = get_json(&format!(
+            "{}/{}/observations?start={}&end={}",
+            STATIONS_API_URL,
+            self.get_station(location)?,
+            (date - window).to_rfc3339_opts(SecondsFormat::Secs, true),
+            (date + window).to_rfc3339_opts(SecondsFormat::Secs, true),
+        ))?;
+
+        let observation = data["features"].as_array().and_then(|features| {
+            features
+                .iter()
+                .map(|e| &e["properties"])
+                .filter_map(|e| Some((parse_time(&e["timestamp"])?, e)))
+                .min_by_key(|(time, _)| (*time - date).num_seconds().abs())
+        });
+
+        match observation {
+            Some((_, observation)) => Ok(parse_observation(observation)),
+            None => Err(WeatherError::Parse(
+                "No historical weather received".to_string(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::json;
+
+    #[test]
+    fn parse_forecast_wind_speed() {
+        assert_eq!(
+            Some(Speed::from_miles_per_hour(10.0)),
+            parse_wind_speed(&json!("10 mph"))
+        );
+        assert_eq!(
+            Some(Speed::from_miles_per_hour(15.0)),
+            parse_wind_speed(&json!("10 to 15 mph"))
+        );
+        assert_eq!(
+            Some(Speed::from_kilometers_per_hour(20.0)),
+            parse_wind_speed(&json!("20 km/h"))
+        );
+        assert_eq!(
+            Some(Speed::from_kilometers_per_hour(9.0)),
+            parse_wind_speed(&json!({ "unitCode": "wmoUnit:km_h-1", "value": 9.0 }))
+        );
+        assert_eq!(None, parse_wind_speed(&json!("calm")));
+        assert_eq!(None, parse_wind_speed(&json!("10 furlongs")));
+        assert_eq!(None, parse_wind_speed(&serde_json::Value::Null));
+    }
+
+    #[test]
+    fn compass_direction_degrees() {
+        assert_eq!(Some(0), compass_degrees("N"));
+        assert_eq!(Some(23), compass_degrees("NNE"));
+        assert_eq!(Some(180), compass_degrees("S"));
+        assert_eq!(Some(338), compass_degrees("NNW"));
+        assert_eq!(None, compass_degrees("north"));
+    }
+
+    #[test]
+    fn cloud_layer_amount() {
+        assert_eq!(Some(0), cloud_amount_percent("CLR"));
+        assert_eq!(Some(44), cloud_amount_percent("SCT"));
+        assert_eq!(Some(100), cloud_amount_percent("VV"));
+        assert_eq!(None, cloud_amount_percent("UNK"));
+    }
+
+    #[test]
+    fn pick_daily_periods() {
+        let period =
+            |start: &str, daytime: bool| json!({ "startTime": start, "isDaytime": daytime });
+        let periods = [
+            // Forecast requested in the evening starts with the night.
+            period("2023-02-14T18:00:00-06:00", false),
+            period("2023-02-15T06:00:00-06:00", true),
+            period("2023-02-15T18:00:00-06:00", false),
+            period("2023-02-16T06:00:00-06:00", true),
+            period("2023-02-16T18:00:00-06:00", false),
+        ];
+
+        let days = daily_periods(&periods);
+        assert_eq!(3, days.len());
+        assert_eq!(&periods[0], days[0]);
+        assert_eq!(&periods[1], days[1]);
+        assert_eq!(&periods[3], days[2]);
+        assert!(daily_periods(&[]).is_empty());
+    }
+}
@@ -0,0 +1,295 @@
+use crate::provider::{
+    check_forecast_days, closest_entry, forecast_date, from_timestamp, get_json,
+    history_not_supported, Forecast, ForecastEntry, Provider, WeatherInfo,
+};
+
+use crate::error::{Result, WeatherError};
+use crate::geocoder::Location;
+use crate::provider::units::{Length, Pressure, Speed, Temperature};
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+
+static FORECAST_API_HOST: &str = "api.open-meteo.com/v1/forecast";
+static ARCHIVE_API_HOST: &str = "archive-api.open-meteo.com/v1/archive";
+
+/// Variables of the current conditions.
+static CURRENT_VARIABLES: &str = "temperature_2m,relative_humidity_2m,apparent_temperature,\
+    precipitation,weather_code,cloud_cover,pressure_msl,wind_speed_10m,wind_direction_10m,\
+    wind_gusts_10m";
+
+/// Variables of the hourly forecast.
+static HOURLY_VARIABLES: &str = "temperature_2m,relative_humidity_2m,dew_point_2m,\
+    apparent_temperature,precipitation_probability,precipitation,weather_code,pressure_msl,\
+    cloud_cover,visibility,wind_speed_10m,wind_direction_10m,wind_gusts_10m,uv_index";
+
+/// Variables of the hourly archive, which lacks forecast-only ones.
+static ARCHIVE_VARIABLES: &str = "temperature_2m,relative_humidity_2m,dew_point_2m,\
+    apparent_temperature,precipitation,weather_code,pressure_msl,cloud_cover,wind_speed_10m,\
+    wind_direction_10m,wind_gusts_10m";
+
+/// Variables of the daily forecast.
+static DAILY_VARIABLES: &str = "weather_code,temperature_2m_max,temperature_2m_min,\
+    apparent_temperature_max,apparent_temperature_min,sunrise,sunset,uv_index_max,\
+    precipitation_sum,precipitation_probability_max,wind_speed_10m_max,wind_gusts_10m_max,\
+    wind_direction_10m_dominant";
+
+/// Forecast horizon of the forecast API.
+const FORECAST_HORIZON_DAYS: u32 = 16;
+
+/// Year the archive starts from.
+const ARCHIVE_START_YEAR: i32 = 1940;
+
+/// Archive lags behind by this many days, the recent past is kept by the forecast API.
+const ARCHIVE_DELAY_DAYS: i64 = 5;
+
+/// Open-Meteo API, free for non-commercial use without a key.
+pub struct OpenMeteo {
+    /// Commercial API key, the free API is used if not specified
+    api_key: Option<String>,
+}
+
+impl OpenMeteo {
+    pub fn new(api_key: Option<String>) -> Self {
+        Self { api_key }
+    }
+
+    /// Makes request URL for the location, the commercial API is requested with the API key.
+    fn url(&self, host: &str, location: &Location) -> String {
+        let mut url = match &self.api_key {
+            Some(api_key) => format!("https://customer-{host}?apikey={api_key}&"),
+            None => format!("https://{host}?"),
+        };
+        url.push_str(&format!(
+            "latitude={}&longitude={}&timezone=UTC&timeformat=unixtime&wind_speed_unit=ms",
+            location.latitude, location.longitude
+        ));
+        url
+    }
+
+    fn get_current(&self, location: &Location) -> Result<WeatherInfo> {
+        // Sunrise and sunset are daily values only.
+        let data = get_json(&format!(
+            "{}&current={}&daily=sunrise,sunset&forecast_days=1",
+            self.url(FORECAST_API_HOST, location),
+            CURRENT_VARIABLES,
+        ))?;
+
+        let current = &data["current"];
+        if !current.is_object() {
+            return Err(WeatherError::Parse(
+                "No weather condition received".to_string(),
+            ));
+        }
+
+        Ok(WeatherInfo {
+            sunrise: from_timestamp(data["daily"]["sunrise"][0].as_i64()),
+            sunset: from_timestamp(data["daily"]["sunset"][0].as_i64()),
+            ..parse_weather(&|name| current[name].as_f64())
+        })
+    }
+
+    /// Gets hourly forecast entries for the specified number of days, starting from today.
+    fn get_hourly_forecast(&self, location: &Location, days: u32) -> Result<Vec<ForecastEntry>> {
+        let data = get_json(&format!(
+            "{}&hourly={}&forecast_days={}",
+            self.url(FORECAST_API_HOST, location),
+            HOURLY_VARIABLES,
+            days,
+        ))?;
+
+        non_empty(parse_entries(&data["hourly"], parse_weather))
+    }
+
+    /// Gets daily forecast entries for the specified number of days, starting from today.
+    fn get_daily_forecast(&self, location: &Location, days: u32) -> Result<Vec<ForecastEntry>> {
+        let data = get_json(&format!(
+            "{}&daily={}&forecast_days={}",
+            self.url(FORECAST_API_HOST, location),
+            DAILY_VARIABLES,
+            days,
+        ))?;
+
+        non_empty(parse_entries(&data["daily"], parse_daily))
+    }
+}
+
+/// Fails if no forecast entries were received.
+fn non_empty(entries: Vec<ForecastEntry>) -> Result<Vec<ForecastEntry>> {
+    if entries.is_empty() {
+        return Err(WeatherError::Parse("No forecast received".to_string()));
+    }
+    Ok(entries)
+}
+
+/// Parses time series of variable arrays into entries, each variable is looked up by name.
+fn parse_entries(
+    series: &serde_json::Value,
+    parse: impl Fn(&dyn Fn(&str) -> Option<f64>) -> WeatherInfo,
+) -> Vec<ForecastEntry> {
+    let times = match series["time"].as_array() {
+        Some(times) => times,
+        None => return Vec::new(),
+    };
+
+    times
+        .iter()
+        .enumerate()
+        .filter_map(|(index, time)| {
+            Some(ForecastEntry {
+                time: from_timestamp(time.as_i64())?,
+                weather: parse(&|name| series[name][index].as_f64()),
+            })
+        })
+        .collect()
+}
+
+/// Parses current or hourly conditions.
+fn parse_weather(value: &dyn Fn(&str) -> Option<f64>) -> WeatherInfo {
+    WeatherInfo {
+        description: value("weather_code").and_then(|e| describe_weather_code(e as i64)),
+        temperature: value("temperature_2m").map(Temperature::from_celsius),
+        humidity: value("relative_humidity_2m").map(|e| e.round() as i64),
+        pressure: value("pressure_msl").map(Pressure::from_hectopascals),
+        feels_like: value("apparent_temperature").map(Temperature::from_celsius),
+        dew_point: value("dew_point_2m").map(Temperature::from_celsius),
+        wind_speed: value("wind_speed_10m").map(Speed::from_meters_per_second),
+        wind_direction: value("wind_direction_10m").map(|e| e.round() as i64),
+        wind_gust: value("wind_gusts_10m").map(Speed::from_meters_per_second),
+        precipitation: value("precipitation").map(Length::from_millimeters),
+        precipitation_probability: value("precipitation_probability").map(|e| e.round() as i64),
+        cloud_cover: value("cloud_cover").map(|e| e.round() as i64),
+        visibility: value("visibility").map(Length::from_meters),
+        uv_index: value("uv_index"),
+        sunrise: None,
+        sunset: None,
+        air_quality: None,
+    }
+}
+
+/// Parses daily forecast, the temperatures are the means of the daily extremes.
+fn parse_daily(value: &dyn Fn(&str) -> Option<f64>) -> WeatherInfo {
+    let mean = |min: &str, max: &str| match (value(min), value(max)) {
+        (Some(min), Some(max)) => Some(Temperature::from_celsius((min + max) / 2.0)),
+        (min, max) => min.or(max).map(Temperature::from_celsius),
+    };
+
+    WeatherInfo {
+        description: value("weather_code").and_then(|e| describe_weather_code(e as i64)),
+        temperature: mean("temperature_2m_min", "temperature_2m_max"),
+        feels_like: mean("apparent_temperature_min", "apparent_temperature_max"),
+        wind_speed: value("wind_speed_10m_max").map(Speed::from_meters_per_second),
+        wind_direction: value("wind_direction_10m_dominant").map(|e| e.round() as i64),
+        wind_gust: value("wind_gusts_10m_max").map(Speed::from_meters_per_second),
+        precipitation: value("precipitation_sum").map(Length::from_millimeters),
+        precipitation_probability: value("precipitation_probability_max").map(|e| e.round() as i64),
+        uv_index: value("uv_index_max"),
+        sunrise: from_timestamp(value("sunrise").map(|e| e as i64)),
+        sunset: from_timestamp(value("sunset").map(|e| e as i64)),
+        ..WeatherInfo::default()
+    }
+}
+
+/// Describes WMO weather interpretation code.
+fn describe_weather_code(code: i64) -> Option<String> {
+    let description = match code {
+        0 => "Clear sky",
+        1 => "Mainly clear",
+        2 => "Partly cloudy",
+        3 => "Overcast",
+        45 => "Fog",
+        48 => "Depositing rime fog",
+        51 => "Light drizzle",
+        53 => "Drizzle",
+        55 => "Dense drizzle",
+        56 | 57 => "Freezing drizzle",
+        61 => "Light rain",
+        63 => "Rain",
+        65 => "Heavy rain",
+        66 | 67 => "Freezing rain",
+        71 => "Light snow",
+        73 => "Snow",
+        75 => "Heavy snow",
+        77 => "Snow grains",
+        80 => "Light rain showers",
+        81 => "Rain showers",
+        82 => "Violent rain showers",
+        85 | 86 => "Snow showers",
+        95 => "Thunderstorm",
+        96 | 99 => "Thunderstorm with hail",
+        _ => return None,
+    };
+    Some(description.to_string())
+}
+
+impl Provider for OpenMeteo {
+    fn get_weather(&self, location: &Location, date: Option<DateTime<Utc>>) -> Result<WeatherInfo> {
+        let date = match forecast_date(date, Duration::days(FORECAST_HORIZON_DAYS.into()))? {
+            Some(date) => date,
+            None => return self.get_current(location),
+        };
+
+        // Forecast days are counted from today, the requested one included.
+        let days = (date.date_naive() - Utc::now().date_naive()).num_days() as u32 + 1;
+        let days = days.min(FORECAST_HORIZON_DAYS);
+        match closest_entry(self.get_hourly_forecast(location, days)?, date) {
+            Some(entry) => Ok(entry.weather),
+            None => Err(WeatherError::Parse("No forecast received".to_string())),
+        }
+    }
+
+    fn get_forecast(&self, location: &Location, days: u32, hourly: bool) -> Result<Forecast> {
+        check_forecast_days(days, FORECAST_HORIZON_DAYS)?;
+
+        let entries = if hourly {
+            // Hours passed today are of no interest.
+            let since = Utc::now() - Duration::hours(1);
+            self.get_hourly_forecast(location, days)?
+                .into_iter()
+                .filter(|e| e.time >= since)
+                .collect()
+        } else {
+            self.get_daily_forecast(location, days)?
+        };
+
+        Ok(Forecast { entries })
+    }
+
+    /// Archive data is available since 1940, the last days are kept by the forecast API.
+    fn get_history(&self, location: &Location, date: DateTime<Utc>) -> Result<WeatherInfo> {
+        let since = Utc
+            .with_ymd_and_hms(ARCHIVE_START_YEAR, 1, 1, 0, 0, 0)
+            .unwrap();
+        if date < since {
+            return Err(history_not_supported(Some(since)));
+        }
+
+        let day = date.format("%Y-%m-%d");
+        let url = if date > Utc::now() - Duration::days(ARCHIVE_DELAY_DAYS) {
+            format!(
+                "{}&hourly={}&start_date={day}&end_date={day}",
+                self.url(FORECAST_API_HOST, location),
+                HOURLY_VARIABLES,
+            )
+        } else {
+            format!(
+                "{}&hourly={}&start_date={day}&end_date={day}",
+                self.url(ARCHIVE_API_HOST, location),
+                ARCHIVE_VARIABLES,
+            )
+        };
+        let data = get_json(&url)?;
+
+        // Hours not observed yet are returned with no values.
+        let entries = parse_entries(&data["hourly"], parse_weather)
+            .into_iter()
+            .filter(|e| e.weather.temperature.is_some())
+            .collect();
+
+        match closest_entry(entries, date) {
+            Some(entry) => Ok(entry.weather),
+            None => Err(WeatherError::Parse(
+                "No historical weather received".to_string(),
+            )),
+        }
+    }
+}
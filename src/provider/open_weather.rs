@@ -1,11 +1,20 @@
-use crate::provider::{Provider, WeatherInfo};
+use crate::provider::{
+    check_forecast_days, closest_entry, daily_entries, forecast_date, from_timestamp, get_json,
+    Forecast, ForecastEntry, Provider, WeatherInfo,
+};
 
-//use chrono::{DateTime, Duration, Utc};
-use reqwest::StatusCode;
-use std::error::Error;
-use string_error::into_err;
+use crate::error::{Result, WeatherError};
+use crate::geocoder::Location;
+use crate::provider::units::{Length, Pressure, Speed, Temperature};
+
+use chrono::{DateTime, Duration, Utc};
 
 static WEATHER_API_URL: &str = "https://api.openweathermap.org/data/2.5/weather";
+static FORECAST_API_URL: &str = "https://api.openweathermap.org/data/2.5/forecast";
+static HISTORY_API_URL: &str = "https://api.openweathermap.org/data/3.0/onecall/timemachine";
+
+/// 5 day / 3 hour forecast horizon.
+const FORECAST_HORIZON_DAYS: u32 = 5;
 
 pub struct OpenWeather {
     api_key: String,
@@ -15,53 +24,189 @@ impl OpenWeather {
     pub fn new(api_key: String) -> Self {
         Self { api_key }
     }
-}
 
-impl Provider for OpenWeather {
-    fn get_weather(
-        &self,
-        address: String,
-        _date: Option<String>,
-    ) -> Result<WeatherInfo, Box<dyn Error>> {
-        let response = reqwest::blocking::get(format!(
-            "{}?units=metric&q={}&appid={}",
-            WEATHER_API_URL, address, self.api_key,
-        ))
-        .map_err(|e| into_err(format!("Request failed ({e})")))
-        .and_then(|r| {
-            if r.status() != StatusCode::OK {
-                return Err(into_err(format!("Request failed ({})", r.status())));
-            }
-            Ok(r)
-        })?;
+    fn get_current(&self, location: &Location) -> Result<WeatherInfo> {
+        let data = get_json(&format!(
+            "{}?units=metric&lat={}&lon={}&appid={}",
+            WEATHER_API_URL, location.latitude, location.longitude, self.api_key,
+        ))?;
 
-        let data = response
-            .json::<serde_json::Value>()
-            .map_err(|e| into_err(format!("Failed to parse response data ({e})")))?;
+        Ok(parse_weather(&data))
+    }
+
+    /// Gets 5 day / 3 hour forecast entries.
+    fn get_forecast_entries(&self, location: &Location) -> Result<Vec<ForecastEntry>> {
+        let data = get_json(&format!(
+            "{}?units=metric&lat={}&lon={}&appid={}",
+            FORECAST_API_URL, location.latitude, location.longitude, self.api_key,
+        ))?;
 
-        let mut info = WeatherInfo {
-            description: None,
-            temperature: None,
-            humidity: None,
-            pressure: None,
+        let entries: Vec<ForecastEntry> = match data["list"].as_array() {
+            Some(list) => list
+                .iter()
+                .filter_map(|e| {
+                    Some(ForecastEntry {
+                        time: from_timestamp(e["dt"].as_i64())?,
+                        weather: parse_weather(e),
+                    })
+                })
+                .collect(),
+            None => Vec::new(),
         };
 
-        if let Some(weather) = data["weather"].as_array() {
-            for e in weather.iter() {
-                let descriptions: &mut String = info.description.get_or_insert(String::from(""));
-                descriptions.push_str(e["main"].as_str().unwrap_or(""))
-            }
-        }
-        if let Some(temperature) = data["main"]["temp"].as_f64() {
-            info.temperature = Some(temperature);
+        if entries.is_empty() {
+            return Err(WeatherError::Parse("No forecast received".to_string()));
         }
-        if let Some(humidity) = data["main"]["humidity"].as_i64() {
-            info.humidity = Some(humidity);
+
+        Ok(entries)
+    }
+}
+
+/// Parses weather entry shared by current weather and forecast responses.
+fn parse_weather(data: &serde_json::Value) -> WeatherInfo {
+    let mut info = WeatherInfo {
+        description: parse_description(data),
+        ..WeatherInfo::default()
+    };
+
+    if let Some(temperature) = data["main"]["temp"].as_f64() {
+        info.temperature = Some(Temperature::from_celsius(temperature));
+    }
+    if let Some(humidity) = data["main"]["humidity"].as_i64() {
+        info.humidity = Some(humidity);
+    }
+    if let Some(pressure) = data["main"]["pressure"].as_f64() {
+        info.pressure = Some(Pressure::from_hectopascals(pressure));
+    }
+
+    info.feels_like = data["main"]["feels_like"]
+        .as_f64()
+        .map(Temperature::from_celsius);
+    info.wind_speed = data["wind"]["speed"]
+        .as_f64()
+        .map(Speed::from_meters_per_second);
+    info.wind_direction = data["wind"]["deg"].as_i64();
+    info.wind_gust = data["wind"]["gust"]
+        .as_f64()
+        .map(Speed::from_meters_per_second);
+    info.precipitation = parse_precipitation(data);
+    // Forecast provides probability as a fraction.
+    info.precipitation_probability = data["pop"].as_f64().map(|e| (e * 100.0).round() as i64);
+    info.cloud_cover = data["clouds"]["all"].as_i64();
+    info.visibility = data["visibility"].as_f64().map(Length::from_meters);
+    info.sunrise = from_timestamp(data["sys"]["sunrise"].as_i64());
+    info.sunset = from_timestamp(data["sys"]["sunset"].as_i64());
+
+    info
+}
+
+/// Parses One Call API entry, which keeps values at the top level unlike the 2.5 API.
+fn parse_one_call_weather(data: &serde_json::Value) -> WeatherInfo {
+    WeatherInfo {
+        description: parse_description(data),
+        temperature: data["temp"].as_f64().map(Temperature::from_celsius),
+        humidity: data["humidity"].as_i64(),
+        pressure: data["pressure"].as_f64().map(Pressure::from_hectopascals),
+        feels_like: data["feels_like"].as_f64().map(Temperature::from_celsius),
+        dew_point: data["dew_point"].as_f64().map(Temperature::from_celsius),
+        wind_speed: data["wind_speed"]
+            .as_f64()
+            .map(Speed::from_meters_per_second),
+        wind_direction: data["wind_deg"].as_i64(),
+        wind_gust: data["wind_gust"]
+            .as_f64()
+            .map(Speed::from_meters_per_second),
+        precipitation: parse_precipitation(data),
+        precipitation_probability: None,
+        cloud_cover: data["clouds"].as_i64(),
+        visibility: data["visibility"].as_f64().map(Length::from_meters),
+        uv_index: data["uvi"].as_f64(),
+        sunrise: from_timestamp(data["sunrise"].as_i64()),
+        sunset: from_timestamp(data["sunset"].as_i64()),
+        air_quality: None,
+    }
+}
+
+fn parse_description(data: &serde_json::Value) -> Option<String> {
+    let mut description = None;
+
+    if let Some(weather) = data["weather"].as_array() {
+        for e in weather.iter() {
+            let descriptions: &mut String = description.get_or_insert(String::from(""));
+            descriptions.push_str(e["main"].as_str().unwrap_or(""))
         }
-        if let Some(pressure) = data["main"]["pressure"].as_i64() {
-            info.pressure = Some(pressure);
+    }
+
+    description
+}
+
+/// Sums up rain and snow volume for the last 1 hour (current) or 3 hours (forecast).
+fn parse_precipitation(data: &serde_json::Value) -> Option<Length> {
+    let volume = |e: &serde_json::Value| e["1h"].as_f64().or_else(|| e["3h"].as_f64());
+
+    match (volume(&data["rain"]), volume(&data["snow"])) {
+        (None, None) => None,
+        (rain, snow) => Some(Length::from_millimeters(
+            rain.unwrap_or(0.0) + snow.unwrap_or(0.0),
+        )),
+    }
+}
+
+impl Provider for OpenWeather {
+    fn get_weather(&self, location: &Location, date: Option<DateTime<Utc>>) -> Result<WeatherInfo> {
+        let date = match forecast_date(date, Duration::days(FORECAST_HORIZON_DAYS.into()))? {
+            Some(date) => date,
+            None => return self.get_current(location),
+        };
+
+        // Pick the 3 hour slot closest to the requested date.
+        match closest_entry(self.get_forecast_entries(location)?, date) {
+            Some(entry) => Ok(entry.weather),
+            None => Err(WeatherError::Parse("No forecast received".to_string())),
         }
+    }
 
-        Ok(info)
+    fn get_forecast(&self, location: &Location, days: u32, hourly: bool) -> Result<Forecast> {
+        check_forecast_days(days, FORECAST_HORIZON_DAYS)?;
+
+        let until = Utc::now() + Duration::days(days.into());
+        let entries: Vec<ForecastEntry> = self
+            .get_forecast_entries(location)?
+            .into_iter()
+            .filter(|e| e.time <= until)
+            .collect();
+
+        Ok(Forecast {
+            // Hourly forecast is not available for free, 3 hour entries are the finest ones.
+            entries: if hourly {
+                entries
+            } else {
+                daily_entries(entries)
+            },
+        })
+    }
+
+    fn get_history(&self, location: &Location, date: DateTime<Utc>) -> Result<WeatherInfo> {
+        let data = get_json(&format!(
+            "{}?units=metric&lat={}&lon={}&dt={}&appid={}",
+            HISTORY_API_URL,
+            location.latitude,
+            location.longitude,
+            date.timestamp(),
+            self.api_key,
+        ))
+        .map_err(|e| match e {
+            WeatherError::Auth(message) => WeatherError::Auth(format!(
+                "{message}; history requires One Call API 3.0 subscription"
+            )),
+            e => e,
+        })?;
+
+        match data["data"].as_array().and_then(|array| array.first()) {
+            Some(entry) => Ok(parse_one_call_weather(entry)),
+            None => Err(WeatherError::Parse(
+                "No historical weather received".to_string(),
+            )),
+        }
     }
 }
@@ -1,6 +1,11 @@
 use crate::arguments;
+use crate::cache::{CacheMode, CacheStorage};
 use crate::provider::accu_weather::AccuWeather;
+use crate::provider::met_norway::MetNorway;
+use crate::provider::nws::Nws;
+use crate::provider::open_meteo::OpenMeteo;
 use crate::provider::open_weather::OpenWeather;
+use crate::provider::weather_api::WeatherApi;
 use crate::provider::{provider_factory, Provider};
 
 /// Provider factory to be used in production.
@@ -8,10 +13,24 @@ pub struct ProductionProviderFactory;
 
 impl provider_factory::ProviderFactory for ProductionProviderFactory {
     /// Makes production weather provider according to specified config
-    fn make_provider(&self, config: &arguments::WeatherProvider) -> Box<dyn Provider> {
+    fn make_provider(
+        &self,
+        config: &arguments::WeatherProvider,
+        cache_storage: Box<dyn CacheStorage>,
+        cache_mode: CacheMode,
+    ) -> Box<dyn Provider> {
+        let api_key = config.api_key.clone().unwrap_or_default();
         match config.provider {
-            arguments::Provider::OpenWeather => Box::new(OpenWeather::new(config.api_key.clone())),
-            arguments::Provider::AccuWeather => Box::new(AccuWeather::new(config.api_key.clone())),
+            arguments::Provider::OpenWeather => Box::new(OpenWeather::new(api_key)),
+            arguments::Provider::AccuWeather => Box::new(AccuWeather::new(api_key, cache_storage)),
+            arguments::Provider::WeatherApi => Box::new(WeatherApi::new(api_key)),
+            arguments::Provider::OpenMeteo => Box::new(OpenMeteo::new(config.api_key.clone())),
+            arguments::Provider::Nws => Box::new(Nws),
+            arguments::Provider::MetNorway => Box::new(MetNorway::new(
+                config.contact.clone(),
+                cache_storage,
+                cache_mode,
+            )),
         }
     }
 }
@@ -1,8 +1,17 @@
 use crate::arguments;
+use crate::cache::{CacheMode, CacheStorage};
 use crate::provider;
 
 /// Weather provider factory
-pub trait ProviderFactory {
-    /// Makes provider based on specified config
-    fn make_provider(&self, config: &arguments::WeatherProvider) -> Box<dyn provider::Provider>;
+///
+/// Shared between threads to make providers queried concurrently.
+pub trait ProviderFactory: Send + Sync {
+    /// Makes provider based on specified config, providers keeping data between runs
+    /// keep it in the cache storage as the cache mode allows.
+    fn make_provider(
+        &self,
+        config: &arguments::WeatherProvider,
+        cache_storage: Box<dyn CacheStorage>,
+        cache_mode: CacheMode,
+    ) -> Box<dyn provider::Provider>;
 }
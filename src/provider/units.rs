@@ -0,0 +1,424 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+/// Unit of measure.
+pub trait Unit: Copy {
+    /// Unit symbol shown after values.
+    fn symbol(&self) -> &'static str;
+
+    /// Maximum number of decimal places shown.
+    fn precision(&self) -> usize;
+}
+
+/// Physical quantity convertible to units of the specific kind.
+pub trait Quantity {
+    type Unit: Unit;
+
+    /// Gets the value in the specified unit.
+    fn value(&self, unit: Self::Unit) -> f64;
+
+    /// Gets the value in the specified unit rounded to the unit precision.
+    fn rounded(&self, unit: Self::Unit) -> f64 {
+        let scale = 10f64.powi(unit.precision() as i32);
+        (self.value(unit) * scale).round() / scale
+    }
+
+    /// Formats the value in the specified unit, trailing zeros are omitted.
+    fn format(&self, unit: Self::Unit) -> String {
+        let value = format!("{:.*}", unit.precision(), self.value(unit));
+        let value = if value.contains('.') {
+            value.trim_end_matches('0').trim_end_matches('.')
+        } else {
+            &value
+        };
+        let value = if value == "-0" { "0" } else { value };
+
+        match unit.symbol() {
+            "°C" | "°F" => format!("{value}{}", unit.symbol()),
+            symbol => format!("{value} {symbol}"),
+        }
+    }
+}
+
+/// Temperature units.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum TemperatureUnit {
+    #[clap(alias = "c")]
+    Celsius,
+
+    #[clap(alias = "f")]
+    Fahrenheit,
+
+    #[clap(alias = "k")]
+    Kelvin,
+}
+
+impl Unit for TemperatureUnit {
+    fn symbol(&self) -> &'static str {
+        match self {
+            TemperatureUnit::Celsius => "°C",
+            TemperatureUnit::Fahrenheit => "°F",
+            TemperatureUnit::Kelvin => "K",
+        }
+    }
+
+    fn precision(&self) -> usize {
+        1
+    }
+}
+
+/// Temperature.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct Temperature {
+    celsius: f64,
+}
+
+impl Temperature {
+    pub fn from_celsius(value: f64) -> Self {
+        Self { celsius: value }
+    }
+
+    pub fn from_fahrenheit(value: f64) -> Self {
+        Self {
+            celsius: (value - 32.0) * 5.0 / 9.0,
+        }
+    }
+
+    pub fn from_kelvin(value: f64) -> Self {
+        Self {
+            celsius: value - 273.15,
+        }
+    }
+}
+
+impl Quantity for Temperature {
+    type Unit = TemperatureUnit;
+
+    fn value(&self, unit: TemperatureUnit) -> f64 {
+        match unit {
+            TemperatureUnit::Celsius => self.celsius,
+            TemperatureUnit::Fahrenheit => self.celsius * 9.0 / 5.0 + 32.0,
+            TemperatureUnit::Kelvin => self.celsius + 273.15,
+        }
+    }
+}
+
+/// Pressure units.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum PressureUnit {
+    /// Hectopascal, equals to millibar
+    #[clap(alias = "hpa", alias = "mbar")]
+    Hectopascal,
+
+    #[clap(alias = "pa")]
+    Pascal,
+
+    #[clap(alias = "inhg")]
+    InchOfMercury,
+
+    #[clap(alias = "mmhg")]
+    MillimeterOfMercury,
+}
+
+impl Unit for PressureUnit {
+    fn symbol(&self) -> &'static str {
+        match self {
+            PressureUnit::Hectopascal => "hPa",
+            PressureUnit::Pascal => "Pa",
+            PressureUnit::InchOfMercury => "inHg",
+            PressureUnit::MillimeterOfMercury => "mmHg",
+        }
+    }
+
+    fn precision(&self) -> usize {
+        match self {
+            PressureUnit::InchOfMercury => 2,
+            _ => 0,
+        }
+    }
+}
+
+/// Pascals in an inch of mercury.
+const PASCALS_PER_INCH_OF_MERCURY: f64 = 3386.389;
+
+/// Pascals in a millimeter of mercury.
+const PASCALS_PER_MILLIMETER_OF_MERCURY: f64 = 133.322;
+
+/// Pressure.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct Pressure {
+    pascals: f64,
+}
+
+impl Pressure {
+    pub fn from_pascals(value: f64) -> Self {
+        Self { pascals: value }
+    }
+
+    /// Hectopascals, equal to millibars.
+    pub fn from_hectopascals(value: f64) -> Self {
+        Self {
+            pascals: value * 100.0,
+        }
+    }
+
+    pub fn from_inches_of_mercury(value: f64) -> Self {
+        Self {
+            pascals: value * PASCALS_PER_INCH_OF_MERCURY,
+        }
+    }
+}
+
+impl Quantity for Pressure {
+    type Unit = PressureUnit;
+
+    fn value(&self, unit: PressureUnit) -> f64 {
+        match unit {
+            PressureUnit::Hectopascal => self.pascals / 100.0,
+            PressureUnit::Pascal => self.pascals,
+            PressureUnit::InchOfMercury => self.pascals / PASCALS_PER_INCH_OF_MERCURY,
+            PressureUnit::MillimeterOfMercury => self.pascals / PASCALS_PER_MILLIMETER_OF_MERCURY,
+        }
+    }
+}
+
+/// Speed units.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum SpeedUnit {
+    #[clap(alias = "ms")]
+    MetersPerSecond,
+
+    #[clap(alias = "kmh")]
+    KilometersPerHour,
+
+    #[clap(alias = "mph")]
+    MilesPerHour,
+
+    #[clap(alias = "kn")]
+    Knots,
+}
+
+impl Unit for SpeedUnit {
+    fn symbol(&self) -> &'static str {
+        match self {
+            SpeedUnit::MetersPerSecond => "m/s",
+            SpeedUnit::KilometersPerHour => "km/h",
+            SpeedUnit::MilesPerHour => "mph",
+            SpeedUnit::Knots => "kn",
+        }
+    }
+
+    fn precision(&self) -> usize {
+        1
+    }
+}
+
+/// Meters in a mile.
+const METERS_PER_MILE: f64 = 1609.344;
+
+/// Meters in a nautical mile.
+const METERS_PER_NAUTICAL_MILE: f64 = 1852.0;
+
+/// Speed.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct Speed {
+    meters_per_second: f64,
+}
+
+impl Speed {
+    pub fn from_meters_per_second(value: f64) -> Self {
+        Self {
+            meters_per_second: value,
+        }
+    }
+
+    pub fn from_kilometers_per_hour(value: f64) -> Self {
+        Self {
+            meters_per_second: value / 3.6,
+        }
+    }
+
+    pub fn from_miles_per_hour(value: f64) -> Self {
+        Self {
+            meters_per_second: value * METERS_PER_MILE / 3600.0,
+        }
+    }
+
+    pub fn from_knots(value: f64) -> Self {
+        Self {
+            meters_per_second: value * METERS_PER_NAUTICAL_MILE / 3600.0,
+        }
+    }
+}
+
+impl Quantity for Speed {
+    type Unit = SpeedUnit;
+
+    fn value(&self, unit: SpeedUnit) -> f64 {
+        match unit {
+            SpeedUnit::MetersPerSecond => self.meters_per_second,
+            SpeedUnit::KilometersPerHour => self.meters_per_second * 3.6,
+            SpeedUnit::MilesPerHour => self.meters_per_second * 3600.0 / METERS_PER_MILE,
+            SpeedUnit::Knots => self.meters_per_second * 3600.0 / METERS_PER_NAUTICAL_MILE,
+        }
+    }
+}
+
+/// Length units.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum LengthUnit {
+    #[clap(alias = "mm")]
+    Millimeter,
+
+    #[clap(alias = "cm")]
+    Centimeter,
+
+    #[clap(alias = "m")]
+    Meter,
+
+    #[clap(alias = "km")]
+    Kilometer,
+
+    #[clap(alias = "in")]
+    Inch,
+
+    #[clap(alias = "mi")]
+    Mile,
+}
+
+impl Unit for LengthUnit {
+    fn symbol(&self) -> &'static str {
+        match self {
+            LengthUnit::Millimeter => "mm",
+            LengthUnit::Centimeter => "cm",
+            LengthUnit::Meter => "m",
+            LengthUnit::Kilometer => "km",
+            LengthUnit::Inch => "in",
+            LengthUnit::Mile => "mi",
+        }
+    }
+
+    fn precision(&self) -> usize {
+        match self {
+            LengthUnit::Millimeter | LengthUnit::Centimeter => 1,
+            LengthUnit::Meter => 4,
+            LengthUnit::Kilometer | LengthUnit::Inch | LengthUnit::Mile => 2,
+        }
+    }
+}
+
+/// Meters in an inch.
+const METERS_PER_INCH: f64 = 0.0254;
+
+/// Length.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct Length {
+    meters: f64,
+}
+
+impl Length {
+    pub fn from_millimeters(value: f64) -> Self {
+        Self {
+            meters: value / 1000.0,
+        }
+    }
+
+    pub fn from_meters(value: f64) -> Self {
+        Self { meters: value }
+    }
+
+    pub fn from_kilometers(value: f64) -> Self {
+        Self {
+            meters: value * 1000.0,
+        }
+    }
+
+    pub fn from_inches(value: f64) -> Self {
+        Self {
+            meters: value * METERS_PER_INCH,
+        }
+    }
+
+    pub fn from_miles(value: f64) -> Self {
+        Self {
+            meters: value * METERS_PER_MILE,
+        }
+    }
+}
+
+impl Quantity for Length {
+    type Unit = LengthUnit;
+
+    fn value(&self, unit: LengthUnit) -> f64 {
+        match unit {
+            LengthUnit::Millimeter => self.meters * 1000.0,
+            LengthUnit::Centimeter => self.meters * 100.0,
+            LengthUnit::Meter => self.meters,
+            LengthUnit::Kilometer => self.meters / 1000.0,
+            LengthUnit::Inch => self.meters / METERS_PER_INCH,
+            LengthUnit::Mile => self.meters / METERS_PER_MILE,
+        }
+    }
+}
+
+/// Unit systems.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum UnitSystem {
+    /// °C, hPa, m/s, mm, km
+    #[default]
+    Metric,
+
+    /// °F, inHg, mph, in, mi
+    Imperial,
+
+    /// K, Pa, m/s, m, m
+    Si,
+}
+
+/// Units values are displayed in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Units {
+    pub temperature: TemperatureUnit,
+    pub pressure: PressureUnit,
+    pub speed: SpeedUnit,
+
+    /// Precipitation amount unit
+    pub precipitation: LengthUnit,
+
+    /// Visibility unit
+    pub distance: LengthUnit,
+}
+
+impl From<UnitSystem> for Units {
+    fn from(system: UnitSystem) -> Self {
+        match system {
+            UnitSystem::Metric => Units {
+                temperature: TemperatureUnit::Celsius,
+                pressure: PressureUnit::Hectopascal,
+                speed: SpeedUnit::MetersPerSecond,
+                precipitation: LengthUnit::Millimeter,
+                distance: LengthUnit::Kilometer,
+            },
+            UnitSystem::Imperial => Units {
+                temperature: TemperatureUnit::Fahrenheit,
+                pressure: PressureUnit::InchOfMercury,
+                speed: SpeedUnit::MilesPerHour,
+                precipitation: LengthUnit::Inch,
+                distance: LengthUnit::Mile,
+            },
+            UnitSystem::Si => Units {
+                temperature: TemperatureUnit::Kelvin,
+                pressure: PressureUnit::Pascal,
+                speed: SpeedUnit::MetersPerSecond,
+                precipitation: LengthUnit::Meter,
+                distance: LengthUnit::Meter,
+            },
+        }
+    }
+}
+
+impl Default for Units {
+    fn default() -> Self {
+        UnitSystem::default().into()
+    }
+}
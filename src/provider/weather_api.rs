@@ -0,0 +1,406 @@
+use crate::provider::{
+    check_forecast_days, closest_entry, forecast_date, from_timestamp, history_not_supported,
+    parse_json, send_get, Forecast, ForecastEntry, Provider, WeatherInfo,
+};
+
+use crate::error::{Result, WeatherError};
+use crate::geocoder::Location;
+use crate::provider::units::{Length, Pressure, Speed, Temperature};
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use reqwest::header::HeaderMap;
+
+static FORECAST_API_URL: &str = "https://api.weatherapi.com/v1/forecast.json";
+static HISTORY_API_URL: &str = "https://api.weatherapi.com/v1/history.json";
+
+/// Forecast horizon of the paid plans, the free plan returns fewer days.
+const FORECAST_HORIZON_DAYS: u32 = 14;
+
+/// Year the history starts from.
+const HISTORY_START_YEAR: i32 = 2010;
+
+/// WeatherAPI.com provider.
+///
+/// Forecast requests return the current conditions, the forecast, astronomy and air quality
+/// at once, so a single request is made for any of them.
+pub struct WeatherApi {
+    api_key: String,
+}
+
+impl WeatherApi {
+    pub fn new(api_key: String) -> Self {
+        Self { api_key }
+    }
+
+    /// Performs request to the API, telling failures apart by the error code in the response.
+    fn request(
+        &self,
+        url: &str,
+        location: &Location,
+        parameters: &str,
+    ) -> Result<serde_json::Value> {
+        let response = send_get(
+            &format!(
+                "{}?key={}&q={:.4},{:.4}&{}",
+                url, self.api_key, location.latitude, location.longitude, parameters
+            ),
+            HeaderMap::new(),
+        )?;
+
+        let status = response.status();
+        if status.is_success() {
+            return parse_json(response);
+        }
+
+        let data = parse_json(response).unwrap_or_default();
+        let message = match data["error"]["message"].as_str() {
+            Some(message) => format!("Request failed ({status}: {message})"),
+            None => return Err(WeatherError::from_status(status)),
+        };
+        // Error codes are listed at https://www.weatherapi.com/docs/#intro-error-codes
+        Err(match data["error"]["code"].as_i64() {
+            Some(1002 | 2006 | 2008 | 2009) => WeatherError::Auth(message),
+            Some(2007) => WeatherError::Quota(message),
+            Some(1006) => WeatherError::NotFound(message),
+            Some(9999) => WeatherError::Network(message),
+            _ => WeatherError::from_status(status).context(message),
+        })
+    }
+
+    /// Gets forecast for the specified number of days, starting from today.
+    fn get_forecast_data(&self, location: &Location, days: u32) -> Result<serde_json::Value> {
+        self.request(
+            FORECAST_API_URL,
+            location,
+            &format!("days={days}&aqi=yes&alerts=no"),
+        )
+    }
+
+    /// Gets hourly history of the local date.
+    fn get_history_data(&self, location: &Location, date: NaiveDate) -> Result<serde_json::Value> {
+        self.request(
+            HISTORY_API_URL,
+            location,
+            &format!("dt={}", date.format("%Y-%m-%d")),
+        )
+    }
+}
+
+/// Gets UTC offset of the location local time, which astronomy times are given in.
+fn utc_offset(location: &serde_json::Value) -> Option<Duration> {
+    let utc = from_timestamp(location["localtime_epoch"].as_i64())?;
+    let local =
+        NaiveDateTime::parse_from_str(location["localtime"].as_str()?, "%Y-%m-%d %H:%M").ok()?;
+
+    // Local time lacks seconds, while offsets are multiples of 15 minutes.
+    let minutes = (local - utc.naive_utc()).num_seconds() as f64 / 60.0;
+    Some(Duration::minutes(((minutes / 15.0).round() * 15.0) as i64))
+}
+
+/// Estimates UTC offset of the local time by the longitude.
+fn solar_offset(longitude: f64) -> Duration {
+    Duration::minutes((longitude * 4.0).round() as i64)
+}
+
+/// Gets local date of the time at the UTC offset.
+fn local_date(time: DateTime<Utc>, offset: Duration) -> NaiveDate {
+    (time + offset).date_naive()
+}
+
+/// Parses astronomy time like "06:45 AM" on the local date, absent for polar days and nights.
+fn parse_astro_time(
+    time: &serde_json::Value,
+    date: NaiveDate,
+    offset: Duration,
+) -> Option<DateTime<Utc>> {
+    let time = NaiveTime::parse_from_str(time.as_str()?, "%I:%M %p").ok()?;
+    Some(Utc.from_utc_datetime(&(date.and_time(time) - offset)))
+}
+
+/// Parses sunrise and sunset of the forecast day.
+fn parse_astro(
+    day: &serde_json::Value,
+    offset: Option<Duration>,
+) -> (Option<DateTime<Utc>>, Option<DateTime<Utc>>) {
+    let date = day["date"]
+        .as_str()
+        .and_then(|e| NaiveDate::parse_from_str(e, "%Y-%m-%d").ok());
+
+    match (date, offset) {
+        (Some(date), Some(offset)) => (
+            parse_astro_time(&day["astro"]["sunrise"], date, offset),
+            parse_astro_time(&day["astro"]["sunset"], date, offset),
+        ),
+        _ => (None, None),
+    }
+}
+
+/// Gets precipitation probability, the chances of rain and snow are given apart.
+fn precipitation_chance(rain: Option<i64>, snow: Option<i64>) -> Option<i64> {
+    match (rain, snow) {
+        (Some(rain), Some(snow)) => Some(rain.max(snow)),
+        (rain, snow) => rain.or(snow),
+    }
+}
+
+/// Parses current or hourly conditions requested with metric units.
+fn parse_condition(condition: &serde_json::Value) -> WeatherInfo {
+    WeatherInfo {
+        description: condition["condition"]["text"]
+            .as_str()
+            .map(|e| e.trim().to_string()),
+        temperature: condition["temp_c"].as_f64().map(Temperature::from_celsius),
+        humidity: condition["humidity"].as_i64(),
+        pressure: condition["pressure_mb"]
+            .as_f64()
+            .map(Pressure::from_hectopascals),
+        feels_like: condition["feelslike_c"]
+            .as_f64()
+            .map(Temperature::from_celsius),
+        dew_point: condition["dewpoint_c"]
+            .as_f64()
+            .map(Temperature::from_celsius),
+        wind_speed: condition["wind_kph"]
+            .as_f64()
+            .map(Speed::from_kilometers_per_hour),
+        wind_direction: condition["wind_degree"].as_i64(),
+        wind_gust: condition["gust_kph"]
+            .as_f64()
+            .map(Speed::from_kilometers_per_hour),
+        precipitation: condition["precip_mm"]
+            .as_f64()
+            .map(Length::from_millimeters),
+        precipitation_probability: precipitation_chance(
+            condition["chance_of_rain"].as_i64(),
+            condition["chance_of_snow"].as_i64(),
+        ),
+        cloud_cover: condition["cloud"].as_i64(),
+        visibility: condition["vis_km"].as_f64().map(Length::from_kilometers),
+        uv_index: condition["uv"].as_f64(),
+        sunrise: None,
+        sunset: None,
+        air_quality: condition["air_quality"]["us-epa-index"].as_i64(),
+    }
+}
+
+/// Parses daily summary of the forecast day.
+fn parse_day(forecast_day: &serde_json::Value, offset: Option<Duration>) -> Option<ForecastEntry> {
+    let day = &forecast_day["day"];
+    let (sunrise, sunset) = parse_astro(forecast_day, offset);
+
+    Some(ForecastEntry {
+        time: from_timestamp(forecast_day["date_epoch"].as_i64())?,
+        weather: WeatherInfo {
+            description: day["condition"]["text"]
+                .as_str()
+                .map(|e| e.trim().to_string()),
+            temperature: day["avgtemp_c"].as_f64().map(Temperature::from_celsius),
+            humidity: day["avghumidity"].as_f64().map(|e| e.round() as i64),
+            wind_speed: day["maxwind_kph"]
+                .as_f64()
+                .map(Speed::from_kilometers_per_hour),
+            precipitation: day["totalprecip_mm"].as_f64().map(Length::from_millimeters),
+            precipitation_probability: precipitation_chance(
+                day["daily_chance_of_rain"].as_i64(),
+                day["daily_chance_of_snow"].as_i64(),
+            ),
+            visibility: day["avgvis_km"].as_f64().map(Length::from_kilometers),
+            uv_index: day["uv"].as_f64(),
+            sunrise,
+            sunset,
+            air_quality: day["air_quality"]["us-epa-index"].as_i64(),
+            ..WeatherInfo::default()
+        },
+    })
+}
+
+/// Parses hourly entries of all the forecast days, along with the astronomy of the day.
+fn parse_hours(data: &serde_json::Value) -> Vec<ForecastEntry> {
+    let offset = utc_offset(&data["location"]);
+
+    let days = match data["forecast"]["forecastday"].as_array() {
+        Some(days) => days,
+        None => return Vec::new(),
+    };
+
+    days.iter()
+        .flat_map(|day| {
+            let (sunrise, sunset) = parse_astro(day, offset);
+            day["hour"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(move |hour| {
+                    Some(ForecastEntry {
+                        time: from_timestamp(hour["time_epoch"].as_i64())?,
+                        weather: WeatherInfo {
+                            sunrise,
+                            sunset,
+                            ..parse_condition(hour)
+                        },
+                    })
+                })
+        })
+        .collect()
+}
+
+/// Picks the hourly entry closest to the date.
+fn closest_hour(data: &serde_json::Value, date: DateTime<Utc>) -> Result<WeatherInfo> {
+    match closest_entry(parse_hours(data), date) {
+        Some(entry) => Ok(entry.weather),
+        None => Err(WeatherError::Parse("No forecast received".to_string())),
+    }
+}
+
+impl Provider for WeatherApi {
+    fn get_weather(&self, location: &Location, date: Option<DateTime<Utc>>) -> Result<WeatherInfo> {
+        let date = match forecast_date(date, Duration::days(FORECAST_HORIZON_DAYS.into()))? {
+            Some(date) => date,
+            None => {
+                let data = self.get_forecast_data(location, 1)?;
+                let current = &data["current"];
+                if !current.is_object() {
+                    return Err(WeatherError::Parse(
+                        "No weather condition received".to_string(),
+                    ));
+                }
+
+                let (sunrise, sunset) = parse_astro(
+                    &data["forecast"]["forecastday"][0],
+                    utc_offset(&data["location"]),
+                );
+                return Ok(WeatherInfo {
+                    sunrise,
+                    sunset,
+                    ..parse_condition(current)
+                });
+            }
+        };
+
+        // Forecast days are counted from the local today, the requested one included. Local
+        // dates may differ from the UTC ones by a day, so an extra day is requested.
+        let days = (date.date_naive() - Utc::now().date_naive()).num_days() as u32 + 2;
+        let days = days.min(FORECAST_HORIZON_DAYS);
+        closest_hour(&self.get_forecast_data(location, days)?, date)
+    }
+
+    fn get_forecast(&self, location: &Location, days: u32, hourly: bool) -> Result<Forecast> {
+        check_forecast_days(days, FORECAST_HORIZON_DAYS)?;
+
+        let data = self.get_forecast_data(location, days)?;
+        let entries: Vec<ForecastEntry> = if hourly {
+            // Hours passed today are of no interest.
+            let since = Utc::now() - Duration::hours(1);
+            parse_hours(&data)
+                .into_iter()
+                .filter(|e| e.time >= since)
+                .collect()
+        } else {
+            let offset = utc_offset(&data["location"]);
+            match data["forecast"]["forecastday"].as_array() {
+                Some(days) => days.iter().filter_map(|e| parse_day(e, offset)).collect(),
+                None => Vec::new(),
+            }
+        };
+
+        if entries.is_empty() {
+            return Err(WeatherError::Parse("No forecast received".to_string()));
+        }
+
+        Ok(Forecast { entries })
+    }
+
+    /// History is available since 2010 on paid plans, the free plan keeps the last week only.
+    fn get_history(&self, location: &Location, date: DateTime<Utc>) -> Result<WeatherInfo> {
+        let since = Utc
+            .with_ymd_and_hms(HISTORY_START_YEAR, 1, 1, 0, 0, 0)
+            .unwrap();
+        if date < since {
+            return Err(history_not_supported(Some(since)));
+        }
+
+        // History is requested by the local date, which is estimated by the longitude first
+        // and requested again if the offset received tells another one.
+        let estimated = local_date(date, solar_offset(location.longitude));
+        let mut data = self.get_history_data(location, estimated)?;
+        if let Some(local) = utc_offset(&data["location"]).map(|e| local_date(date, e)) {
+            if local != estimated {
+                data = self.get_history_data(location, local)?;
+            }
+        }
+
+        closest_hour(&data, date)
+            .map_err(|_| WeatherError::Parse("No historical weather received".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::json;
+
+    #[test]
+    fn utc_offset_of_local_time() {
+        // 2023-02-14T12:00:00Z
+        let location = json!({ "localtime_epoch": 1676376000, "localtime": "2023-02-14 17:30" });
+        assert_eq!(Some(Duration::minutes(330)), utc_offset(&location));
+
+        // Local time is given a few seconds after the epoch one, without seconds.
+        let location = json!({ "localtime_epoch": 1676376045, "localtime": "2023-02-13 23:00" });
+        assert_eq!(Some(Duration::hours(-13)), utc_offset(&location));
+
+        assert_eq!(
+            None,
+            utc_offset(&json!({ "localtime": "2023-02-14 17:30" }))
+        );
+        assert_eq!(
+            None,
+            utc_offset(&json!({ "localtime_epoch": 1676376000, "localtime": "17:30" }))
+        );
+    }
+
+    #[test]
+    fn local_date_at_offset() {
+        let time = Utc.with_ymd_and_hms(2023, 2, 14, 20, 0, 0).unwrap();
+        let date = NaiveDate::from_ymd_opt(2023, 2, 14).unwrap();
+
+        assert_eq!(
+            date.succ_opt().unwrap(),
+            local_date(time, solar_offset(151.21))
+        );
+        assert_eq!(date, local_date(time, solar_offset(-122.42)));
+        assert_eq!(date, local_date(time, Duration::zero()));
+    }
+
+    #[test]
+    fn parse_astronomy_time() {
+        let date = NaiveDate::from_ymd_opt(2023, 2, 14).unwrap();
+
+        assert_eq!(
+            Some(Utc.with_ymd_and_hms(2023, 2, 14, 5, 15, 0).unwrap()),
+            parse_astro_time(&json!("07:15 AM"), date, Duration::hours(2))
+        );
+        assert_eq!(
+            Some(Utc.with_ymd_and_hms(2023, 2, 15, 1, 40, 0).unwrap()),
+            parse_astro_time(&json!("06:40 PM"), date, Duration::hours(-7))
+        );
+        assert_eq!(
+            None,
+            parse_astro_time(&json!("No sunrise"), date, Duration::zero())
+        );
+        assert_eq!(
+            None,
+            parse_astro_time(&serde_json::Value::Null, date, Duration::zero())
+        );
+    }
+
+    #[test]
+    fn precipitation_chance_of_rain_or_snow() {
+        assert_eq!(Some(40), precipitation_chance(Some(40), Some(10)));
+        assert_eq!(Some(70), precipitation_chance(Some(0), Some(70)));
+        assert_eq!(Some(20), precipitation_chance(Some(20), None));
+        assert_eq!(Some(5), precipitation_chance(None, Some(5)));
+        assert_eq!(None, precipitation_chance(None, None));
+    }
+}
@@ -0,0 +1,52 @@
+use weather::arguments::{parse_address, parse_postal_code, Address};
+
+#[test]
+fn parse_names() {
+    assert_eq!(
+        Address::Name("Kyiv".to_string()),
+        parse_address(" Kyiv ").unwrap()
+    );
+    assert_eq!(
+        Address::Name("Paris, FR".to_string()),
+        parse_address("Paris, FR").unwrap()
+    );
+}
+
+#[test]
+fn parse_coordinates() {
+    assert_eq!(
+        Address::Coordinates {
+            latitude: 50.45,
+            longitude: 30.52
+        },
+        parse_address("50.45,30.52").unwrap()
+    );
+    assert_eq!(
+        Address::Coordinates {
+            latitude: -33.87,
+            longitude: 151.21
+        },
+        parse_address("-33.87, 151.21").unwrap()
+    );
+    assert!(parse_address("91,30").is_err());
+    assert!(parse_address("50,181").is_err());
+}
+
+#[test]
+fn parse_postal_codes() {
+    assert_eq!(
+        Address::PostalCode {
+            code: "02000".to_string(),
+            country: Some("UA".to_string())
+        },
+        parse_postal_code("02000,ua").unwrap()
+    );
+    assert_eq!(
+        Address::PostalCode {
+            code: "10001".to_string(),
+            country: None
+        },
+        parse_postal_code("10001").unwrap()
+    );
+    assert!(parse_postal_code(",UA").is_err());
+}
@@ -0,0 +1,319 @@
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use mockall::mock;
+use serde_json::Value;
+
+use weather::cache::{self, CacheMode, CacheStats, CacheStorage, CachedValue, CachingProvider};
+use weather::error::WeatherError;
+use weather::geocoder::Location;
+use weather::provider::units::{Pressure, Temperature};
+use weather::provider::{FallbackProvider, Forecast, ForecastEntry, Provider, WeatherInfo};
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use reqwest::StatusCode;
+
+mock! {
+    Provider {}
+    impl Provider for Provider {
+        fn get_weather(
+            &self,
+            location: &Location,
+            date: Option<DateTime<Utc>>,
+        ) -> Result<WeatherInfo, WeatherError>;
+        fn get_forecast(
+            &self,
+            location: &Location,
+            days: u32,
+            hourly: bool,
+        ) -> Result<Forecast, WeatherError>;
+    }
+}
+
+/// In-memory cache storage, entries expire on demand only.
+#[derive(Clone, Default)]
+struct MemoryCacheStorage {
+    entries: Arc<Mutex<HashMap<String, (CachedValue, bool)>>>,
+}
+
+impl MemoryCacheStorage {
+    /// Makes all entries expired, storing them two hours ago.
+    fn expire(&self) {
+        for (cached, expired) in self.entries.lock().unwrap().values_mut() {
+            cached.stored_at = Utc::now() - Duration::hours(2);
+            *expired = true;
+        }
+    }
+}
+
+impl CacheStorage for MemoryCacheStorage {
+    fn get(&self, key: &str) -> Result<Option<Value>, WeatherError> {
+        Ok(self
+            .entries
+            .lock()
+            .unwrap()
+            .get(key)
+            .filter(|e| !e.1)
+            .map(|e| e.0.value.clone()))
+    }
+
+    fn get_last(&self, key: &str) -> Result<Option<CachedValue>, WeatherError> {
+        Ok(self.entries.lock().unwrap().get(key).map(|e| e.0.clone()))
+    }
+
+    fn put(&self, key: &str, value: &Value, _: Duration) -> Result<(), WeatherError> {
+        let cached = CachedValue {
+            value: value.clone(),
+            stored_at: Utc::now(),
+        };
+        self.entries
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), (cached, false));
+        Ok(())
+    }
+
+    fn remove(&self, key: &str) -> Result<(), WeatherError> {
+        self.entries.lock().unwrap().remove(key);
+        Ok(())
+    }
+
+    fn stats(&self) -> Result<CacheStats, WeatherError> {
+        let entries = self.entries.lock().unwrap();
+        Ok(CacheStats {
+            entries: entries.len(),
+            expired: entries.values().filter(|e| e.1).count(),
+            ..CacheStats::default()
+        })
+    }
+
+    fn clear(&self) -> Result<usize, WeatherError> {
+        Ok(self.entries.lock().unwrap().drain().count())
+    }
+
+    fn clear_scope(&self, scope: &str) -> Result<usize, WeatherError> {
+        let mut entries = self.entries.lock().unwrap();
+        let count = entries.len();
+        entries.retain(|key, _| cache::key_scope(key) != Some(scope));
+        Ok(count - entries.len())
+    }
+}
+
+fn kyiv() -> Location {
+    Location {
+        name: "Kyiv".to_string(),
+        latitude: 50.45,
+        longitude: 30.52,
+        ..Location::default()
+    }
+}
+
+fn weather() -> WeatherInfo {
+    WeatherInfo {
+        description: Some("Clear".to_string()),
+        temperature: Some(Temperature::from_kelvin(274.37)),
+        pressure: Some(Pressure::from_pascals(101_234.0)),
+        sunrise: Some(Utc.with_ymd_and_hms(2023, 2, 14, 5, 12, 0).unwrap()),
+        ..WeatherInfo::default()
+    }
+}
+
+#[test]
+fn cached_weather_round_trip() {
+    let storage = MemoryCacheStorage::default();
+
+    let mut provider_mock = MockProvider::new();
+    provider_mock
+        .expect_get_weather()
+        .times(1)
+        .returning(|_, _| Ok(weather()));
+    let provider = CachingProvider::new(
+        Box::new(provider_mock),
+        "test",
+        Box::new(storage.clone()),
+        CacheMode::Use,
+    );
+
+    provider.get_weather(&kyiv(), None).unwrap();
+    let cached = provider.get_weather(&kyiv(), None).unwrap();
+
+    // Values are cached unrounded.
+    assert_eq!(weather().temperature, cached.temperature);
+    assert_eq!(weather().pressure, cached.pressure);
+    assert_eq!(weather().sunrise, cached.sunrise);
+    assert_eq!(weather().description, cached.description);
+    assert_eq!(1, storage.stats().unwrap().entries);
+}
+
+#[test]
+fn cached_forecast_keyed_by_query() {
+    let storage = MemoryCacheStorage::default();
+
+    let mut provider_mock = MockProvider::new();
+    provider_mock
+        .expect_get_forecast()
+        .times(2)
+        .returning(|_, _, _| {
+            Ok(Forecast {
+                entries: vec![ForecastEntry {
+                    time: Utc.with_ymd_and_hms(2023, 2, 14, 12, 0, 0).unwrap(),
+                    weather: weather(),
+                }],
+            })
+        });
+    let provider = CachingProvider::new(
+        Box::new(provider_mock),
+        "test",
+        Box::new(storage.clone()),
+        CacheMode::Use,
+    );
+
+    provider.get_forecast(&kyiv(), 2, false).unwrap();
+    provider.get_forecast(&kyiv(), 2, true).unwrap();
+    let cached = provider.get_forecast(&kyiv(), 2, false).unwrap();
+
+    assert_eq!(1, cached.entries.len());
+    assert_eq!(weather().temperature, cached.entries[0].weather.temperature);
+    assert_eq!(2, storage.stats().unwrap().entries);
+}
+
+#[test]
+fn refreshed_weather_not_read() {
+    let storage = MemoryCacheStorage::default();
+
+    let mut provider_mock = MockProvider::new();
+    provider_mock
+        .expect_get_weather()
+        .times(2)
+        .returning(|_, _| Ok(weather()));
+    let provider = CachingProvider::new(
+        Box::new(provider_mock),
+        "test",
+        Box::new(storage.clone()),
+        CacheMode::Refresh,
+    );
+
+    provider.get_weather(&kyiv(), None).unwrap();
+    provider.get_weather(&kyiv(), None).unwrap();
+
+    assert_eq!(1, storage.stats().unwrap().entries);
+}
+
+#[test]
+fn cached_weather_keeps_provider_answered() {
+    let storage = MemoryCacheStorage::default();
+
+    let group = |fetched: bool| {
+        let mut failing_mock = MockProvider::new();
+        failing_mock
+            .expect_get_weather()
+            .times(usize::from(fetched))
+            .returning(|_, _| Err(WeatherError::from_status(StatusCode::SERVICE_UNAVAILABLE)));
+        let mut answering_mock = MockProvider::new();
+        answering_mock
+            .expect_get_weather()
+            .times(usize::from(fetched))
+            .returning(|_, _| Ok(weather()));
+
+        CachingProvider::new(
+            FallbackProvider::new(vec![
+                (
+                    "owm".to_string(),
+                    Box::new(failing_mock) as Box<dyn Provider>,
+                ),
+                ("accu".to_string(), Box::new(answering_mock)),
+            ]),
+            "group",
+            Box::new(storage.clone()),
+            CacheMode::Use,
+        )
+    };
+
+    let provider = group(true);
+    provider.get_weather(&kyiv(), None).unwrap();
+    assert_eq!(Some("accu".to_string()), provider.answered());
+
+    // Cached response is still reported as answered by the provider it came from.
+    let provider = group(false);
+    provider.get_weather(&kyiv(), None).unwrap();
+    assert_eq!(Some("accu".to_string()), provider.answered());
+}
+
+/// Makes the provider caching the weather of the provider mock.
+fn caching_provider(
+    storage: &MemoryCacheStorage,
+    mode: CacheMode,
+    provider_expect: fn(&mut MockProvider),
+) -> CachingProvider<Box<MockProvider>> {
+    let mut provider_mock = MockProvider::new();
+    provider_expect(&mut provider_mock);
+    CachingProvider::new(
+        Box::new(provider_mock),
+        "test",
+        Box::new(storage.clone()),
+        mode,
+    )
+}
+
+#[test]
+fn offline_weather_served_stale() {
+    let storage = MemoryCacheStorage::default();
+
+    let provider = caching_provider(&storage, CacheMode::Use, |provider_mock| {
+        provider_mock
+            .expect_get_weather()
+            .times(1)
+            .returning(|_, _| Ok(weather()));
+    });
+    provider.get_weather(&kyiv(), None).unwrap();
+    assert_eq!(None, provider.stale_since());
+
+    // Unexpired data is fresh even offline.
+    let provider = caching_provider(&storage, CacheMode::Offline, |provider_mock| {
+        provider_mock.expect_get_weather().times(0);
+    });
+    provider.get_weather(&kyiv(), None).unwrap();
+    assert_eq!(None, provider.stale_since());
+
+    storage.expire();
+    let cached = provider.get_weather(&kyiv(), None).unwrap();
+    assert_eq!(weather().temperature, cached.temperature);
+    let age = Utc::now() - provider.stale_since().unwrap();
+    assert!(age >= Duration::hours(2));
+
+    // Nothing is stored for other queries.
+    assert!(provider.get_forecast(&kyiv(), 1, false).is_err());
+}
+
+#[test]
+fn network_error_served_stale() {
+    let storage = MemoryCacheStorage::default();
+
+    let provider = caching_provider(&storage, CacheMode::Use, |provider_mock| {
+        provider_mock
+            .expect_get_weather()
+            .times(1)
+            .returning(|_, _| Ok(weather()));
+    });
+    provider.get_weather(&kyiv(), None).unwrap();
+    storage.expire();
+
+    let provider = caching_provider(&storage, CacheMode::Use, |provider_mock| {
+        provider_mock
+            .expect_get_weather()
+            .times(1)
+            .returning(|_, _| Err(WeatherError::Network("no connectivity".to_string())));
+    });
+    assert!(provider.get_weather(&kyiv(), None).is_ok());
+    assert!(provider.stale_since().is_some());
+
+    // Rejected requests are not masked with stored data.
+    let provider = caching_provider(&storage, CacheMode::Use, |provider_mock| {
+        provider_mock
+            .expect_get_weather()
+            .times(1)
+            .returning(|_, _| Err(WeatherError::from_status(StatusCode::UNAUTHORIZED)));
+    });
+    assert!(provider.get_weather(&kyiv(), None).is_err());
+    assert_eq!(None, provider.stale_since());
+}
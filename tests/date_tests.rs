@@ -0,0 +1,91 @@
+use chrono::{DateTime, Duration, Utc};
+use clap::Parser;
+
+use weather::arguments::{parse_date, Cli, WeatherCommand};
+use weather::error::WeatherError;
+
+fn now() -> DateTime<Utc> {
+    "2023-02-14T09:30:00Z".parse().unwrap()
+}
+
+#[test]
+fn parse_keywords() {
+    assert_eq!(now(), parse_date("now", now()).unwrap());
+    assert_eq!(now(), parse_date("Today", now()).unwrap());
+    assert_eq!(
+        now() + Duration::days(1),
+        parse_date("tomorrow", now()).unwrap()
+    );
+    assert_eq!(
+        now() - Duration::days(1),
+        parse_date("yesterday", now()).unwrap()
+    );
+}
+
+#[test]
+fn parse_relative() {
+    assert_eq!(now() + Duration::days(3), parse_date("+3d", now()).unwrap());
+    assert_eq!(
+        now() + Duration::hours(12),
+        parse_date("+12h", now()).unwrap()
+    );
+    assert_eq!(
+        now() - Duration::minutes(30),
+        parse_date("-30m", now()).unwrap()
+    );
+    assert_eq!(
+        now() + Duration::weeks(1),
+        parse_date("+1w", now()).unwrap()
+    );
+
+    assert!(parse_date("+3", now()).is_err());
+    assert!(parse_date("+3y", now()).is_err());
+    assert!(parse_date("+d", now()).is_err());
+}
+
+#[test]
+fn parse_offset_out_of_range() {
+    for value in [
+        "+99999999999999d",
+        "+9999999999d",
+        "-9999999999w",
+        "+99999999999999999999m",
+    ] {
+        match parse_date(value, now()) {
+            Err(WeatherError::Parse(_)) => {}
+            _ => panic!("'{value}' must be rejected as unparsable"),
+        }
+    }
+}
+
+#[test]
+fn accept_past_offset_argument() {
+    let cli = Cli::try_parse_from(["weather", "get", "Kyiv", "--date", "-3h"]).unwrap();
+
+    match cli.command {
+        WeatherCommand::Get { date, .. } => assert_eq!(Some("-3h".to_string()), date),
+        _ => panic!("get command expected"),
+    }
+}
+
+#[test]
+fn parse_absolute() {
+    let expected: DateTime<Utc> = "2023-02-16T13:00:00Z".parse().unwrap();
+
+    assert_eq!(
+        expected,
+        parse_date("2023-02-16T15:00:00+02:00", now()).unwrap()
+    );
+    assert_eq!(expected, parse_date("2023-02-16T13:00:00Z", now()).unwrap());
+    assert_eq!(expected, parse_date("2023-02-16T13:00", now()).unwrap());
+    assert_eq!(expected, parse_date("2023-02-16 13:00", now()).unwrap());
+
+    assert_eq!(
+        "2023-02-16T12:00:00Z".parse::<DateTime<Utc>>().unwrap(),
+        parse_date("2023-02-16", now()).unwrap()
+    );
+    assert_eq!(now(), parse_date("2023-02-14", now()).unwrap());
+
+    assert!(parse_date("16.02.2023", now()).is_err());
+    assert!(parse_date("2023-02-30", now()).is_err());
+}
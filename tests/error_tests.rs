@@ -0,0 +1,50 @@
+use reqwest::StatusCode;
+
+use weather::error::WeatherError;
+
+#[test]
+fn error_kind_from_status() {
+    assert_eq!(
+        WeatherError::Auth("Request failed (401 Unauthorized)".to_string()),
+        WeatherError::from_status(StatusCode::UNAUTHORIZED)
+    );
+    assert_eq!(
+        WeatherError::Quota("Request failed (429 Too Many Requests)".to_string()),
+        WeatherError::from_status(StatusCode::TOO_MANY_REQUESTS)
+    );
+    assert_eq!(
+        WeatherError::Network("Request failed (503 Service Unavailable)".to_string()),
+        WeatherError::from_status(StatusCode::SERVICE_UNAVAILABLE)
+    );
+    assert_eq!(
+        WeatherError::NotFound("Request failed (404 Not Found)".to_string()),
+        WeatherError::from_status(StatusCode::NOT_FOUND)
+    );
+    assert_eq!(
+        WeatherError::Parse("Request failed (400 Bad Request)".to_string()),
+        WeatherError::from_status(StatusCode::BAD_REQUEST)
+    );
+    assert_eq!(
+        WeatherError::Parse("Request failed (422 Unprocessable Entity)".to_string()),
+        WeatherError::from_status(StatusCode::UNPROCESSABLE_ENTITY)
+    );
+}
+
+#[test]
+fn error_context_keeps_kind() {
+    let error = WeatherError::NotFound("Not found".to_string()).context("Provider 'owm' not found");
+
+    assert_eq!(
+        WeatherError::NotFound("Provider 'owm' not found (Not found)".to_string()),
+        error
+    );
+    assert_eq!(4, error.exit_code());
+}
+
+#[test]
+fn transient_errors() {
+    assert!(WeatherError::Network("no connectivity".to_string()).is_transient());
+    assert!(WeatherError::from_status(StatusCode::TOO_MANY_REQUESTS).is_transient());
+    assert!(!WeatherError::from_status(StatusCode::FORBIDDEN).is_transient());
+    assert!(!WeatherError::Parse("Failed to parse response".to_string()).is_transient());
+}
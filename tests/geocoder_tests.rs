@@ -0,0 +1,87 @@
+use weather::geocoder::{GeoNamesGeocoder, Geocoder};
+use weather::provider::units::{LengthUnit, Quantity};
+
+use std::path::PathBuf;
+
+fn geocoder() -> GeoNamesGeocoder {
+    GeoNamesGeocoder::new(
+        [
+            env!("CARGO_MANIFEST_DIR"),
+            "tests/data/geonames/cities_test.txt",
+        ]
+        .iter()
+        .collect::<PathBuf>(),
+    )
+}
+
+#[test]
+fn geonames_exact_match() {
+    let locations = geocoder().search("Paris").unwrap();
+
+    assert_eq!(2, locations.len());
+    // More populated city goes first.
+    assert_eq!(Some("FR"), locations[0].country.as_deref());
+    assert_eq!(Some("Île-de-France"), locations[0].admin_area.as_deref());
+    assert_eq!(Some("Europe/Paris"), locations[0].timezone.as_deref());
+    assert_eq!(Some("Texas"), locations[1].admin_area.as_deref());
+}
+
+#[test]
+fn geonames_alternate_and_fuzzy_names() {
+    let locations = geocoder().search("kiev").unwrap();
+    assert_eq!(1, locations.len());
+    assert_eq!("Kyiv", locations[0].name);
+    assert_eq!(Some("Kyiv City"), locations[0].admin_area.as_deref());
+
+    let locations = geocoder().search("Zurich").unwrap();
+    assert_eq!("Zürich", locations[0].name);
+
+    let locations = geocoder().search("Springfeild").unwrap();
+    assert_eq!(2, locations.len());
+    assert!(geocoder().search("Atlantis").unwrap().is_empty());
+}
+
+#[test]
+fn geonames_qualified_search() {
+    let locations = geocoder().search("Springfield, MO").unwrap();
+    assert_eq!(1, locations.len());
+    assert_eq!(Some("Missouri"), locations[0].admin_area.as_deref());
+
+    let locations = geocoder().search("Paris, US").unwrap();
+    assert_eq!(1, locations.len());
+    assert_eq!(Some("Texas"), locations[0].admin_area.as_deref());
+}
+
+#[test]
+fn geonames_id_lookup() {
+    let location = geocoder().get_location("703448").unwrap();
+    assert_eq!("Kyiv", location.name);
+    assert_eq!(50.45466, location.latitude);
+    assert!(geocoder().get_location("1").is_err());
+}
+
+#[test]
+fn geonames_reverse() {
+    // Brovary is ~20 km east of Kyiv.
+    let location = geocoder().reverse(50.51, 30.79).unwrap().unwrap();
+    assert_eq!("Kyiv", location.name);
+
+    let distance = location.distance(50.51, 30.79).value(LengthUnit::Kilometer);
+    assert!((19.0..21.0).contains(&distance), "{distance}");
+
+    // Versailles is closer to Paris, France than to Zürich.
+    let location = geocoder().reverse(48.80, 2.13).unwrap().unwrap();
+    assert_eq!(Some("FR"), location.country.as_deref());
+}
+
+#[test]
+fn geonames_reverse_skips_invalid_points() {
+    // The dump has a city with invalid coordinates, which is never the nearest one.
+    let geocoder = geocoder();
+    let location = geocoder.reverse(50.51, 30.79).unwrap().unwrap();
+    assert_eq!("Kyiv", location.name);
+
+    // Index built once serves the following lookups.
+    let location = geocoder.reverse(47.37, 8.54).unwrap().unwrap();
+    assert_eq!("Zürich", location.name);
+}
@@ -0,0 +1,237 @@
+use weather::error::WeatherError;
+use weather::output::{
+    render_comparison, render_forecast, render_stale_notice, render_weather, Output, OutputFormat,
+    Summary, Template,
+};
+use weather::provider::units::{Pressure, Temperature, UnitSystem, Units};
+use weather::provider::{Forecast, ForecastEntry, WeatherInfo};
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+
+fn weather() -> WeatherInfo {
+    WeatherInfo {
+        description: Some("Rain, heavy".to_string()),
+        temperature: Some(Temperature::from_celsius(1.5)),
+        humidity: Some(80),
+        pressure: Some(Pressure::from_hectopascals(1013.0)),
+        ..WeatherInfo::default()
+    }
+}
+
+fn time() -> DateTime<Utc> {
+    "2023-02-14T12:00:00Z".parse().unwrap()
+}
+
+#[test]
+fn render_json() {
+    let output = render_weather(
+        &weather(),
+        time(),
+        Units::default(),
+        &Output::Format(OutputFormat::Json),
+    )
+    .unwrap();
+    let document: serde_json::Value = serde_json::from_str(&output).unwrap();
+
+    assert_eq!(1, document["version"]);
+    assert_eq!("°C", document["units"]["temperature"]);
+    assert_eq!(1, document["entries"].as_array().unwrap().len());
+
+    let entry = &document["entries"][0];
+    assert_eq!("2023-02-14T12:00:00Z", entry["time"]);
+    assert_eq!("Rain, heavy", entry["description"]);
+    assert_eq!(1.5, entry["temperature"]);
+    assert_eq!(80, entry["humidity"]);
+    assert_eq!(1013.0, entry["pressure"]);
+    assert!(entry["wind_speed"].is_null());
+}
+
+#[test]
+fn render_ndjson() {
+    let forecast = Forecast {
+        entries: vec![
+            ForecastEntry {
+                time: time(),
+                weather: weather(),
+            },
+            ForecastEntry {
+                time: "2023-02-14T15:00:00Z".parse().unwrap(),
+                weather: WeatherInfo::default(),
+            },
+        ],
+    };
+
+    let output = render_forecast(
+        &forecast,
+        UnitSystem::Imperial.into(),
+        &Output::Format(OutputFormat::Ndjson),
+    )
+    .unwrap();
+    let lines: Vec<serde_json::Value> = output
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+
+    assert_eq!(2, lines.len());
+    assert_eq!(1, lines[0]["version"]);
+    assert_eq!("°F", lines[0]["units"]["temperature"]);
+    assert_eq!(34.7, lines[0]["temperature"]);
+    assert_eq!("2023-02-14T15:00:00Z", lines[1]["time"]);
+    assert!(lines[1]["temperature"].is_null());
+}
+
+#[test]
+fn render_csv() {
+    let output = render_weather(
+        &weather(),
+        time(),
+        Units::default(),
+        &Output::Format(OutputFormat::Csv),
+    )
+    .unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+
+    assert_eq!(
+        "version,time,description,temperature,feels_like,dew_point,humidity,pressure,\
+         wind_speed,wind_direction,wind_gust,precipitation,precipitation_probability,\
+         cloud_cover,visibility,uv_index,sunrise,sunset,air_quality",
+        lines[0]
+    );
+    assert_eq!(
+        "1,2023-02-14T12:00:00Z,\"Rain, heavy\",1.5,,,80,1013,,,,,,,,,,,",
+        lines[1]
+    );
+}
+
+#[test]
+fn render_table() {
+    let output = render_weather(
+        &weather(),
+        time(),
+        Units::default(),
+        &Output::Format(OutputFormat::Table),
+    )
+    .unwrap();
+
+    assert_eq!(
+        "time                  description  temperature (°C)  humidity (%)  pressure (hPa)\n\
+         2023-02-14T12:00:00Z  Rain, heavy  1.5               80            1013\n",
+        output
+    );
+}
+
+#[test]
+fn render_yaml() {
+    let output = render_weather(
+        &weather(),
+        time(),
+        Units::default(),
+        &Output::Format(OutputFormat::Yaml),
+    )
+    .unwrap();
+
+    assert!(output.starts_with("version: 1\n"));
+    assert!(output.contains("temperature: 1.5\n"));
+}
+
+#[test]
+fn render_template() {
+    let template = Template::parse(
+        "{time:%H:%M} {description}: {temperature:.1}{temperature_unit}, \
+         wind {wind_speed} {{m/s}}",
+    )
+    .unwrap();
+    let output = Output::Template {
+        template,
+        placeholder: "n/a".to_string(),
+    };
+
+    assert_eq!(
+        "12:00 Rain, heavy: 1.5°C, wind n/a {m/s}\n",
+        render_weather(&weather(), time(), Units::default(), &output).unwrap()
+    );
+
+    let template = Template::parse("{pressure:>6.1}|{humidity:<4}|").unwrap();
+    let output = Output::Template {
+        template,
+        placeholder: String::new(),
+    };
+
+    assert_eq!(
+        "1013.0|80  |\n",
+        render_weather(&weather(), time(), Units::default(), &output).unwrap()
+    );
+}
+
+#[test]
+fn parse_invalid_template() {
+    assert!(Template::parse("{temp}").is_err());
+    assert!(Template::parse("{temperature").is_err());
+    assert!(Template::parse("temperature}").is_err());
+    assert!(Template::parse("{temperature:.x}").is_err());
+}
+
+#[test]
+fn parse_invalid_time_format() {
+    assert!(Template::parse("{time:%Q}").is_err());
+    assert!(Template::parse("{sunrise:%H:%Q}").is_err());
+    assert!(Template::parse("{sunrise:%H:%M}").is_ok());
+}
+
+#[test]
+fn render_stale_data_notice() {
+    let now = Utc.with_ymd_and_hms(2023, 2, 14, 12, 0, 0).unwrap();
+
+    assert_eq!(
+        "Stored data from 2023-02-14 09:30 UTC (2 hours old)",
+        render_stale_notice(now - Duration::minutes(150), now)
+    );
+    assert_eq!(
+        "Stored data from 2023-02-13 12:00 UTC (1 day old)",
+        render_stale_notice(now - Duration::days(1), now)
+    );
+}
+
+#[test]
+fn summarize_values() {
+    assert_eq!(None, Summary::of(&[1.5]));
+    assert_eq!(
+        Some(Summary {
+            median: 2.0,
+            mean: 3.0,
+            spread: 6.0,
+        }),
+        Summary::of(&[7.0, 1.0, 2.0, 2.0])
+    );
+}
+
+#[test]
+fn render_provider_comparison() {
+    let other = WeatherInfo {
+        description: Some("Rain".to_string()),
+        temperature: Some(Temperature::from_celsius(2.4)),
+        humidity: Some(75),
+        pressure: Some(Pressure::from_hectopascals(1015.0)),
+        ..WeatherInfo::default()
+    };
+    let results = vec![
+        ("owm".to_string(), Ok(weather())),
+        ("accu".to_string(), Ok(other)),
+        (
+            "spare".to_string(),
+            Err(WeatherError::Network(
+                "Request failed (503 Service Unavailable)".to_string(),
+            )),
+        ),
+    ];
+
+    assert_eq!(
+        "field             owm          accu  median  mean  spread\n\
+         description       Rain, heavy  Rain\n\
+         temperature (°C)  1.5          2.4   1.95    1.95  0.9\n\
+         humidity (%)      80           75    77.5    77.5  5\n\
+         pressure (hPa)    1013         1015  1014    1014  2\n\
+         spare: Request failed (503 Service Unavailable)\n",
+        render_comparison(&results, Units::default())
+    );
+}
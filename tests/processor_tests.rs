@@ -1,21 +1,40 @@
+use chrono::{DateTime, Utc};
 use mockall::{mock, predicate::eq};
-use string_error::static_err;
 
 use weather::arguments::{
-    self, Cli, DataStorage, ProviderCommand, ProviderSubcommand, WeatherCommand, WeatherProvider,
+    self, AddressArgs, CacheArgs, CacheCommand, CacheSubcommand, Cli, DataStorage, GeocoderConfig,
+    GroupCommand, GroupSubcommand, LocationCommand, LocationSubcommand, OutputArgs,
+    ProviderCommand, ProviderGroup, ProviderSubcommand, SavedLocation, UnitsArgs, WeatherCommand,
+    WeatherProvider,
 };
-use weather::processor::{DependencyFactory, Processor};
-use weather::provider::{Provider, ProviderFactory, WeatherInfo};
+use weather::cache::{CacheMode, CacheStats, CacheStorage, CachedValue};
+use weather::error::WeatherError;
+use weather::geocoder::{Geocoder, GeocoderFactory, Location};
+use weather::processor::{DependencyFactory, LocationPicker, Processor};
+use weather::provider::units::{Speed, Temperature};
+use weather::provider::{Forecast, ForecastEntry, Provider, ProviderFactory, WeatherInfo};
 
-use std::error::Error;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 mock! {
     DataStorage {}
     impl DataStorage for DataStorage {
-        fn get_all_providers(&self) -> Result<Vec<WeatherProvider>, Box<dyn Error>>;
-        fn get_provider(&self, name: &str) -> Result<WeatherProvider, Box<dyn Error>>;
-        fn add_provider(&self, provider: &WeatherProvider) -> Result<(), Box<dyn Error>>;
-        fn remove_provider(&self, name: &str) -> Result<(), Box<dyn Error>>;
+        fn get_all_providers(&self) -> Result<Vec<WeatherProvider>, WeatherError>;
+        fn get_provider(&self, name: &str) -> Result<WeatherProvider, WeatherError>;
+        fn add_provider(&self, provider: &WeatherProvider) -> Result<(), WeatherError>;
+        fn remove_provider(&self, name: &str) -> Result<(), WeatherError>;
+        fn get_default_provider(&self) -> Result<Option<String>, WeatherError>;
+        fn set_default_provider(&self, name: &str) -> Result<(), WeatherError>;
+        fn get_all_locations(&self) -> Result<Vec<SavedLocation>, WeatherError>;
+        fn get_location(&self, name: &str) -> Result<SavedLocation, WeatherError>;
+        fn add_location(&self, location: &SavedLocation) -> Result<(), WeatherError>;
+        fn remove_location(&self, name: &str) -> Result<(), WeatherError>;
+        fn get_geocoder(&self) -> Result<GeocoderConfig, WeatherError>;
+        fn set_geocoder(&self, geocoder: &GeocoderConfig) -> Result<(), WeatherError>;
+        fn get_all_groups(&self) -> Result<Vec<ProviderGroup>, WeatherError>;
+        fn get_group(&self, name: &str) -> Result<ProviderGroup, WeatherError>;
+        fn add_group(&self, group: &ProviderGroup) -> Result<(), WeatherError>;
+        fn remove_group(&self, name: &str) -> Result<(), WeatherError>;
     }
 }
 
@@ -24,26 +43,128 @@ mock! {
     impl Provider for Provider {
         fn get_weather(
             &self,
-            address: String,
-            date: Option<String>,
-        ) -> Result<WeatherInfo, Box<dyn Error>>;
+            location: &Location,
+            date: Option<DateTime<Utc>>,
+        ) -> Result<WeatherInfo, WeatherError>;
+        fn get_forecast(
+            &self,
+            location: &Location,
+            days: u32,
+            hourly: bool,
+        ) -> Result<Forecast, WeatherError>;
+        fn get_history(
+            &self,
+            location: &Location,
+            date: DateTime<Utc>,
+        ) -> Result<WeatherInfo, WeatherError>;
+    }
+}
+
+mock! {
+    Geocoder{}
+    impl Geocoder for Geocoder {
+        fn search(&self, address: &str) -> Result<Vec<Location>, WeatherError>;
+        fn search_postal_code<'a>(
+            &self,
+            code: &str,
+            country: Option<&'a str>,
+        ) -> Result<Vec<Location>, WeatherError>;
+        fn reverse(&self, latitude: f64, longitude: f64) -> Result<Option<Location>, WeatherError>;
+        fn get_location(&self, id: &str) -> Result<Location, WeatherError>;
+    }
+}
+
+mock! {
+    CacheStorage{}
+    impl CacheStorage for CacheStorage {
+        fn get(&self, key: &str) -> Result<Option<serde_json::Value>, WeatherError>;
+        fn get_last(&self, key: &str) -> Result<Option<CachedValue>, WeatherError>;
+        fn put(&self, key: &str, value: &serde_json::Value, ttl: chrono::Duration) -> Result<(), WeatherError>;
+        fn remove(&self, key: &str) -> Result<(), WeatherError>;
+        fn stats(&self) -> Result<CacheStats, WeatherError>;
+        fn clear(&self) -> Result<usize, WeatherError>;
+        fn clear_scope(&self, scope: &str) -> Result<usize, WeatherError>;
+    }
+}
+
+mock! {
+    LocationPicker{}
+    impl LocationPicker for LocationPicker {
+        fn pick(&self, address: &str, locations: &[Location]) -> Result<Option<usize>, WeatherError>;
+    }
+}
+
+fn kyiv() -> Location {
+    Location {
+        name: "Kyiv".to_string(),
+        country: Some("UA".to_string()),
+        latitude: 50.45,
+        longitude: 30.52,
+        ..Location::default()
     }
 }
 
+/// Expects Kyiv to be resolved once.
+fn expect_kyiv(mut geocoder_mock: Box<MockGeocoder>) -> Box<MockGeocoder> {
+    geocoder_mock
+        .expect_search()
+        .with(eq("Kyiv"))
+        .times(1)
+        .returning(|_| Ok(vec![kyiv()]));
+    geocoder_mock
+}
+
+/// Expects no saved locations and the default geocoder config.
+fn expect_default_locations(data_storage_mock: &mut MockDataStorage) {
+    data_storage_mock
+        .expect_get_location()
+        .returning(|_| Err(WeatherError::NotFound("Not found".to_string())));
+    data_storage_mock
+        .expect_get_geocoder()
+        .returning(|| Ok(GeocoderConfig::Provider));
+}
+
+/// Expects nothing cached, storing anything.
+fn expect_empty_cache(mut cache_storage_mock: Box<MockCacheStorage>) -> Box<MockCacheStorage> {
+    cache_storage_mock.expect_get().returning(|_| Ok(None));
+    cache_storage_mock.expect_get_last().returning(|_| Ok(None));
+    cache_storage_mock.expect_put().returning(|_, _, _| Ok(()));
+    cache_storage_mock
+}
+
 struct TestableProviderFactory {
     pub provider_expect: fn(Box<MockProvider>) -> Box<MockProvider>,
 }
 
 impl ProviderFactory for TestableProviderFactory {
-    fn make_provider(&self, _: &WeatherProvider) -> Box<dyn Provider> {
+    fn make_provider(
+        &self,
+        _: &WeatherProvider,
+        _: Box<dyn CacheStorage>,
+        _: CacheMode,
+    ) -> Box<dyn Provider> {
         let provider_mock = Box::new(MockProvider::new());
         (self.provider_expect)(provider_mock)
     }
 }
 
+struct TestableGeocoderFactory {
+    pub geocoder_expect: fn(Box<MockGeocoder>) -> Box<MockGeocoder>,
+}
+
+impl GeocoderFactory for TestableGeocoderFactory {
+    fn make_geocoder(&self, _: &GeocoderConfig, _: &WeatherProvider) -> Box<dyn Geocoder> {
+        let geocoder_mock = Box::new(MockGeocoder::new());
+        (self.geocoder_expect)(geocoder_mock)
+    }
+}
+
 struct TestableDependencyFactory {
     pub data_storage_expect: fn(Box<MockDataStorage>) -> Box<MockDataStorage>,
     pub provider_expect: fn(Box<MockProvider>) -> Box<MockProvider>,
+    pub geocoder_expect: fn(Box<MockGeocoder>) -> Box<MockGeocoder>,
+    pub cache_storage_expect: fn(Box<MockCacheStorage>) -> Box<MockCacheStorage>,
+    pub location_picker_expect: fn(Box<MockLocationPicker>) -> Box<MockLocationPicker>,
 }
 
 impl DependencyFactory for TestableDependencyFactory {
@@ -57,6 +178,22 @@ impl DependencyFactory for TestableDependencyFactory {
             provider_expect: self.provider_expect,
         })
     }
+
+    fn make_geocoder_factory(&self) -> Box<dyn GeocoderFactory> {
+        Box::new(TestableGeocoderFactory {
+            geocoder_expect: self.geocoder_expect,
+        })
+    }
+
+    fn make_cache_storage(&self) -> Box<dyn CacheStorage> {
+        let cache_storage_mock = Box::new(MockCacheStorage::new());
+        (self.cache_storage_expect)(cache_storage_mock)
+    }
+
+    fn make_location_picker(&self) -> Box<dyn LocationPicker> {
+        let location_picker_mock = Box::new(MockLocationPicker::new());
+        (self.location_picker_expect)(location_picker_mock)
+    }
 }
 
 #[test]
@@ -67,17 +204,20 @@ fn add_provider() {
                 .expect_get_provider()
                 .with(eq("add_provider_test".to_string()))
                 .times(1)
-                .returning(|_| Err(static_err("err")));
+                .returning(|_| Err(WeatherError::NotFound("err".to_string())));
 
             data_storage_mock
                 .expect_add_provider()
-                .withf(|p| p.name == "add_provider_test".to_string())
+                .withf(|p| p.name == "add_provider_test")
                 .times(1)
                 .returning(|_| Ok(()));
 
             data_storage_mock
         },
         provider_expect: |provider_mock| provider_mock,
+        geocoder_expect: |geocoder_mock| geocoder_mock,
+        cache_storage_expect: expect_empty_cache,
+        location_picker_expect: |location_picker_mock| location_picker_mock,
     });
 
     let processor = Processor::new(dependency_factory);
@@ -87,7 +227,8 @@ fn add_provider() {
             command: ProviderSubcommand::Add(WeatherProvider {
                 name: "add_provider_test".to_string(),
                 provider: arguments::Provider::AccuWeather,
-                api_key: "api_key".to_string(),
+                api_key: Some("api_key".to_string()),
+                contact: None,
             }),
         }),
     };
@@ -107,7 +248,8 @@ fn add_existing_provider() {
                     Ok(WeatherProvider {
                         name: "add_existing_provider_test".to_string(),
                         provider: arguments::Provider::AccuWeather,
-                        api_key: "api_key".to_string(),
+                        api_key: Some("api_key".to_string()),
+                        contact: None,
                     })
                 });
 
@@ -116,6 +258,9 @@ fn add_existing_provider() {
             data_storage_mock
         },
         provider_expect: |provider_mock| provider_mock,
+        geocoder_expect: |geocoder_mock| geocoder_mock,
+        cache_storage_expect: expect_empty_cache,
+        location_picker_expect: |location_picker_mock| location_picker_mock,
     });
 
     let processor = Processor::new(dependency_factory);
@@ -125,18 +270,139 @@ fn add_existing_provider() {
             command: ProviderSubcommand::Add(WeatherProvider {
                 name: "add_existing_provider_test".to_string(),
                 provider: arguments::Provider::AccuWeather,
-                api_key: "api_key".to_string(),
+                api_key: Some("api_key".to_string()),
+                contact: None,
+            }),
+        }),
+    };
+
+    assert!(processor.run(add_provider_command).is_err());
+}
+
+#[test]
+fn add_keyless_provider() {
+    let dependency_factory = Box::new(TestableDependencyFactory {
+        data_storage_expect: |mut data_storage_mock| {
+            data_storage_mock
+                .expect_get_provider()
+                .with(eq("add_keyless_provider_test".to_string()))
+                .times(1)
+                .returning(|_| Err(WeatherError::NotFound("err".to_string())));
+
+            data_storage_mock
+                .expect_add_provider()
+                .withf(|p| p.name == "add_keyless_provider_test" && p.api_key.is_none())
+                .times(1)
+                .returning(|_| Ok(()));
+
+            data_storage_mock
+        },
+        provider_expect: |provider_mock| provider_mock,
+        geocoder_expect: |geocoder_mock| geocoder_mock,
+        cache_storage_expect: expect_empty_cache,
+        location_picker_expect: |location_picker_mock| location_picker_mock,
+    });
+
+    let processor = Processor::new(dependency_factory);
+
+    let add_provider_command = Cli {
+        command: WeatherCommand::Provider(ProviderCommand {
+            command: ProviderSubcommand::Add(WeatherProvider {
+                name: "add_keyless_provider_test".to_string(),
+                provider: arguments::Provider::OpenMeteo,
+                api_key: None,
+                contact: None,
             }),
         }),
     };
 
-    assert_eq!(false, processor.run(add_provider_command).is_ok());
+    assert_eq!(Some(()), processor.run(add_provider_command).ok());
+}
+
+#[test]
+fn add_provider_without_required_key() {
+    let dependency_factory = Box::new(TestableDependencyFactory {
+        data_storage_expect: |mut data_storage_mock| {
+            data_storage_mock.expect_add_provider().times(0);
+
+            data_storage_mock
+        },
+        provider_expect: |provider_mock| provider_mock,
+        geocoder_expect: |geocoder_mock| geocoder_mock,
+        cache_storage_expect: expect_empty_cache,
+        location_picker_expect: |location_picker_mock| location_picker_mock,
+    });
+
+    let processor = Processor::new(dependency_factory);
+
+    let add_provider_command = Cli {
+        command: WeatherCommand::Provider(ProviderCommand {
+            command: ProviderSubcommand::Add(WeatherProvider {
+                name: "add_provider_without_required_key_test".to_string(),
+                provider: arguments::Provider::OpenWeather,
+                api_key: None,
+                contact: None,
+            }),
+        }),
+    };
+
+    assert_eq!(
+        Some(3),
+        processor
+            .run(add_provider_command)
+            .err()
+            .map(|e| e.exit_code())
+    );
+}
+
+#[test]
+fn add_provider_without_required_contact() {
+    let dependency_factory = Box::new(TestableDependencyFactory {
+        data_storage_expect: |mut data_storage_mock| {
+            data_storage_mock.expect_add_provider().times(0);
+
+            data_storage_mock
+        },
+        provider_expect: |provider_mock| provider_mock,
+        geocoder_expect: |geocoder_mock| geocoder_mock,
+        cache_storage_expect: expect_empty_cache,
+        location_picker_expect: |location_picker_mock| location_picker_mock,
+    });
+
+    let processor = Processor::new(dependency_factory);
+
+    let add_provider_command = Cli {
+        command: WeatherCommand::Provider(ProviderCommand {
+            command: ProviderSubcommand::Add(WeatherProvider {
+                name: "add_provider_without_required_contact_test".to_string(),
+                provider: arguments::Provider::MetNorway,
+                api_key: None,
+                contact: None,
+            }),
+        }),
+    };
+
+    assert_eq!(
+        Some(3),
+        processor
+            .run(add_provider_command)
+            .err()
+            .map(|e| e.exit_code())
+    );
 }
 
 #[test]
 fn remove_provider() {
     let dependency_factory = Box::new(TestableDependencyFactory {
         data_storage_expect: |mut data_storage_mock| {
+            data_storage_mock
+                .expect_get_all_groups()
+                .times(1)
+                .returning(|| Ok(vec![]));
+            data_storage_mock
+                .expect_get_all_locations()
+                .times(1)
+                .returning(|| Ok(vec![]));
             data_storage_mock
                 .expect_remove_provider()
                 .with(eq("remove_provider_test".to_string()))
@@ -146,6 +412,9 @@ fn remove_provider() {
             data_storage_mock
         },
         provider_expect: |provider_mock| provider_mock,
+        geocoder_expect: |geocoder_mock| geocoder_mock,
+        cache_storage_expect: expect_empty_cache,
+        location_picker_expect: |location_picker_mock| location_picker_mock,
     });
 
     let processor = Processor::new(dependency_factory);
@@ -161,6 +430,56 @@ fn remove_provider() {
     assert_eq!(Some(()), processor.run(remove_provider_command).ok());
 }
 
+#[test]
+fn remove_provider_in_use() {
+    let dependency_factory = Box::new(TestableDependencyFactory {
+        data_storage_expect: |mut data_storage_mock| {
+            data_storage_mock
+                .expect_get_all_groups()
+                .times(1)
+                .returning(|| {
+                    Ok(vec![ProviderGroup {
+                        name: "group".to_string(),
+                        providers: vec!["owm".to_string(), "accu".to_string()],
+                    }])
+                });
+            data_storage_mock
+                .expect_get_all_locations()
+                .times(1)
+                .returning(|| {
+                    Ok(vec![SavedLocation {
+                        name: "home".to_string(),
+                        location: kyiv(),
+                        provider: Some("accu".to_string()),
+                    }])
+                });
+            data_storage_mock.expect_remove_provider().times(0);
+
+            data_storage_mock
+        },
+        provider_expect: |provider_mock| provider_mock,
+        geocoder_expect: |geocoder_mock| geocoder_mock,
+        cache_storage_expect: expect_empty_cache,
+        location_picker_expect: |location_picker_mock| location_picker_mock,
+    });
+
+    let processor = Processor::new(dependency_factory);
+
+    let remove_provider_command = Cli {
+        command: WeatherCommand::Provider(ProviderCommand {
+            command: ProviderSubcommand::Remove {
+                name: "accu".to_string(),
+            },
+        }),
+    };
+
+    let error = processor.run(remove_provider_command).err().unwrap();
+    assert_eq!(3, error.exit_code());
+    assert!(error
+        .to_string()
+        .contains("used by group 'group', location 'home'"));
+}
+
 #[test]
 fn show_provider() {
     let dependency_factory = Box::new(TestableDependencyFactory {
@@ -169,11 +488,14 @@ fn show_provider() {
                 .expect_get_provider()
                 .with(eq("show_provider_test".to_string()))
                 .times(1)
-                .returning(|_| Err(static_err("err")));
+                .returning(|_| Err(WeatherError::NotFound("err".to_string())));
 
             data_storage_mock
         },
         provider_expect: |provider_mock| provider_mock,
+        geocoder_expect: |geocoder_mock| geocoder_mock,
+        cache_storage_expect: expect_empty_cache,
+        location_picker_expect: |location_picker_mock| location_picker_mock,
     });
 
     let processor = Processor::new(dependency_factory);
@@ -193,6 +515,8 @@ fn show_provider() {
 fn get_weather() {
     let dependency_factory = Box::new(TestableDependencyFactory {
         data_storage_expect: |mut data_storage_mock| {
+            expect_default_locations(&mut data_storage_mock);
+
             data_storage_mock
                 .expect_get_provider()
                 .with(eq("get_weather_test".to_string()))
@@ -202,7 +526,8 @@ fn get_weather() {
                     Ok(WeatherProvider {
                         name: "get_weather_test".to_string(),
                         provider: arguments::Provider::AccuWeather,
-                        api_key: "api_key".to_string(),
+                        api_key: Some("api_key".to_string()),
+                        contact: None,
                     })
                 });
 
@@ -211,29 +536,1529 @@ fn get_weather() {
         provider_expect: |mut provider_mock| {
             provider_mock
                 .expect_get_weather()
-                .with(eq("Kyiv".to_string()), eq(None))
+                .with(eq(kyiv()), eq(None))
                 .times(1)
-                .returning(|_, _| {
-                    Ok(WeatherInfo {
-                        description: None,
-                        temperature: None,
-                        humidity: None,
-                        pressure: None,
+                .returning(|_, _| Ok(WeatherInfo::default()));
+            provider_mock
+        },
+        geocoder_expect: expect_kyiv,
+        cache_storage_expect: expect_empty_cache,
+        location_picker_expect: |location_picker_mock| location_picker_mock,
+    });
+
+    let processor = Processor::new(dependency_factory);
+
+    let get_weather_command = Cli {
+        command: WeatherCommand::Get {
+            address: AddressArgs::from("Kyiv"),
+            date: None,
+            provider_name: Some("get_weather_test".to_string()),
+            units: UnitsArgs::default(),
+            output: OutputArgs::default(),
+            cache: CacheArgs::default(),
+        },
+    };
+
+    assert_eq!(Some(()), processor.run(get_weather_command).ok());
+}
+
+#[test]
+fn get_weather_for_date() {
+    let dependency_factory = Box::new(TestableDependencyFactory {
+        data_storage_expect: |mut data_storage_mock| {
+            expect_default_locations(&mut data_storage_mock);
+
+            data_storage_mock
+                .expect_get_provider()
+                .with(eq("get_weather_for_date_test".to_string()))
+                .times(1)
+                .returning(|_| {
+                    Ok(WeatherProvider {
+                        name: "get_weather_for_date_test".to_string(),
+                        provider: arguments::Provider::OpenWeather,
+                        api_key: Some("api_key".to_string()),
+                        contact: None,
                     })
                 });
+
+            data_storage_mock
+        },
+        provider_expect: |mut provider_mock| {
+            provider_mock
+                .expect_get_weather()
+                .withf(|location, date| {
+                    location.name == "Kyiv"
+                        && *date == Some("2030-01-02T15:00:00Z".parse::<DateTime<Utc>>().unwrap())
+                })
+                .times(1)
+                .returning(|_, _| Ok(WeatherInfo::default()));
             provider_mock
         },
+        geocoder_expect: expect_kyiv,
+        cache_storage_expect: expect_empty_cache,
+        location_picker_expect: |location_picker_mock| location_picker_mock,
     });
 
     let processor = Processor::new(dependency_factory);
 
     let get_weather_command = Cli {
         command: WeatherCommand::Get {
-            address: "Kyiv".to_string(),
-            date: None,
-            provider_name: "get_weather_test".to_string(),
+            address: AddressArgs::from("Kyiv"),
+            date: Some("2030-01-02T17:00:00+02:00".to_string()),
+            provider_name: Some("get_weather_for_date_test".to_string()),
+            units: UnitsArgs::default(),
+            output: OutputArgs::default(),
+            cache: CacheArgs::default(),
         },
     };
 
     assert_eq!(Some(()), processor.run(get_weather_command).ok());
 }
+
+#[test]
+fn get_weather_invalid_date() {
+    let dependency_factory = Box::new(TestableDependencyFactory {
+        data_storage_expect: |data_storage_mock| data_storage_mock,
+        provider_expect: |mut provider_mock| {
+            provider_mock.expect_get_weather().times(0);
+            provider_mock
+        },
+        geocoder_expect: |geocoder_mock| geocoder_mock,
+        cache_storage_expect: expect_empty_cache,
+        location_picker_expect: |location_picker_mock| location_picker_mock,
+    });
+
+    let processor = Processor::new(dependency_factory);
+
+    let get_weather_command = Cli {
+        command: WeatherCommand::Get {
+            address: AddressArgs::from("Kyiv"),
+            date: Some("next thursday".to_string()),
+            provider_name: Some("get_weather_invalid_date_test".to_string()),
+            units: UnitsArgs::default(),
+            output: OutputArgs::default(),
+            cache: CacheArgs::default(),
+        },
+    };
+
+    assert!(processor.run(get_weather_command).is_err());
+}
+
+#[test]
+fn get_forecast() {
+    let dependency_factory = Box::new(TestableDependencyFactory {
+        data_storage_expect: |mut data_storage_mock| {
+            expect_default_locations(&mut data_storage_mock);
+
+            data_storage_mock
+                .expect_get_provider()
+                .with(eq("get_forecast_test".to_string()))
+                .times(1)
+                .returning(|_| {
+                    Ok(WeatherProvider {
+                        name: "get_forecast_test".to_string(),
+                        provider: arguments::Provider::OpenWeather,
+                        api_key: Some("api_key".to_string()),
+                        contact: None,
+                    })
+                });
+
+            data_storage_mock
+        },
+        provider_expect: |mut provider_mock| {
+            provider_mock
+                .expect_get_forecast()
+                .with(eq(kyiv()), eq(3), eq(true))
+                .times(1)
+                .returning(|_, _, _| {
+                    Ok(Forecast {
+                        entries: vec![ForecastEntry {
+                            time: "2030-01-02T15:00:00Z".parse().unwrap(),
+                            weather: WeatherInfo {
+                                temperature: Some(Temperature::from_celsius(1.5)),
+                                wind_speed: Some(Speed::from_meters_per_second(3.2)),
+                                ..WeatherInfo::default()
+                            },
+                        }],
+                    })
+                });
+            provider_mock
+        },
+        geocoder_expect: expect_kyiv,
+        cache_storage_expect: expect_empty_cache,
+        location_picker_expect: |location_picker_mock| location_picker_mock,
+    });
+
+    let processor = Processor::new(dependency_factory);
+
+    let get_forecast_command = Cli {
+        command: WeatherCommand::Forecast {
+            address: AddressArgs::from("Kyiv"),
+            days: 3,
+            hourly: true,
+            provider_name: Some("get_forecast_test".to_string()),
+            units: UnitsArgs::default(),
+            output: OutputArgs::default(),
+            cache: CacheArgs::default(),
+        },
+    };
+
+    assert_eq!(Some(()), processor.run(get_forecast_command).ok());
+}
+
+#[test]
+fn get_weather_history() {
+    let dependency_factory = Box::new(TestableDependencyFactory {
+        data_storage_expect: |mut data_storage_mock| {
+            expect_default_locations(&mut data_storage_mock);
+
+            data_storage_mock
+                .expect_get_provider()
+                .with(eq("get_weather_history_test".to_string()))
+                .times(1)
+                .returning(|_| {
+                    Ok(WeatherProvider {
+                        name: "get_weather_history_test".to_string(),
+                        provider: arguments::Provider::OpenWeather,
+                        api_key: Some("api_key".to_string()),
+                        contact: None,
+                    })
+                });
+
+            data_storage_mock
+        },
+        provider_expect: |mut provider_mock| {
+            provider_mock.expect_get_weather().times(0);
+            provider_mock
+                .expect_get_history()
+                .withf(|location, date| {
+                    location.name == "Kyiv"
+                        && *date == "2020-01-02T12:00:00Z".parse::<DateTime<Utc>>().unwrap()
+                })
+                .times(1)
+                .returning(|_, _| {
+                    Err(WeatherError::Unsupported(
+                        "History is not supported by this provider".to_string(),
+                    ))
+                });
+            provider_mock
+        },
+        geocoder_expect: expect_kyiv,
+        cache_storage_expect: expect_empty_cache,
+        location_picker_expect: |location_picker_mock| location_picker_mock,
+    });
+
+    let processor = Processor::new(dependency_factory);
+
+    let get_weather_command = Cli {
+        command: WeatherCommand::Get {
+            address: AddressArgs::from("Kyiv"),
+            date: Some("2020-01-02".to_string()),
+            provider_name: Some("get_weather_history_test".to_string()),
+            units: UnitsArgs::default(),
+            output: OutputArgs::default(),
+            cache: CacheArgs::default(),
+        },
+    };
+
+    // Provider errors are reported with their kind.
+    assert_eq!(
+        Some(9),
+        processor
+            .run(get_weather_command)
+            .err()
+            .map(|e| e.exit_code())
+    );
+}
+
+#[test]
+fn set_default_provider() {
+    let dependency_factory = Box::new(TestableDependencyFactory {
+        data_storage_expect: |mut data_storage_mock| {
+            data_storage_mock
+                .expect_set_default_provider()
+                .with(eq("set_default_provider_test".to_string()))
+                .times(1)
+                .returning(|_| Ok(()));
+
+            data_storage_mock
+        },
+        provider_expect: |provider_mock| provider_mock,
+        geocoder_expect: |geocoder_mock| geocoder_mock,
+        cache_storage_expect: expect_empty_cache,
+        location_picker_expect: |location_picker_mock| location_picker_mock,
+    });
+
+    let processor = Processor::new(dependency_factory);
+
+    let set_default_provider_command = Cli {
+        command: WeatherCommand::Provider(ProviderCommand {
+            command: ProviderSubcommand::Default {
+                name: "set_default_provider_test".to_string(),
+            },
+        }),
+    };
+
+    assert_eq!(Some(()), processor.run(set_default_provider_command).ok());
+}
+
+#[test]
+fn get_weather_default_provider() {
+    let dependency_factory = Box::new(TestableDependencyFactory {
+        data_storage_expect: |mut data_storage_mock| {
+            expect_default_locations(&mut data_storage_mock);
+
+            data_storage_mock
+                .expect_get_default_provider()
+                .times(1)
+                .returning(|| Ok(Some("get_weather_default_provider_test".to_string())));
+
+            data_storage_mock
+                .expect_get_provider()
+                .with(eq("get_weather_default_provider_test".to_string()))
+                .times(1)
+                .returning(|_| {
+                    Ok(WeatherProvider {
+                        name: "get_weather_default_provider_test".to_string(),
+                        provider: arguments::Provider::OpenWeather,
+                        api_key: Some("api_key".to_string()),
+                        contact: None,
+                    })
+                });
+
+            data_storage_mock.expect_get_all_providers().times(0);
+
+            data_storage_mock
+        },
+        provider_expect: |mut provider_mock| {
+            provider_mock
+                .expect_get_weather()
+                .times(1)
+                .returning(|_, _| Ok(WeatherInfo::default()));
+            provider_mock
+        },
+        geocoder_expect: expect_kyiv,
+        cache_storage_expect: expect_empty_cache,
+        location_picker_expect: |location_picker_mock| location_picker_mock,
+    });
+
+    let processor = Processor::new(dependency_factory);
+
+    let get_weather_command = Cli {
+        command: WeatherCommand::Get {
+            address: AddressArgs::from("Kyiv"),
+            date: None,
+            provider_name: None,
+            units: UnitsArgs::default(),
+            output: OutputArgs::default(),
+            cache: CacheArgs::default(),
+        },
+    };
+
+    assert_eq!(Some(()), processor.run(get_weather_command).ok());
+}
+
+#[test]
+fn get_weather_single_provider() {
+    let dependency_factory = Box::new(TestableDependencyFactory {
+        data_storage_expect: |mut data_storage_mock| {
+            expect_default_locations(&mut data_storage_mock);
+
+            data_storage_mock
+                .expect_get_default_provider()
+                .times(1)
+                .returning(|| Ok(None));
+
+            data_storage_mock
+                .expect_get_all_providers()
+                .times(1)
+                .returning(|| {
+                    Ok(vec![WeatherProvider {
+                        name: "get_weather_single_provider_test".to_string(),
+                        provider: arguments::Provider::OpenWeather,
+                        api_key: Some("api_key".to_string()),
+                        contact: None,
+                    }])
+                });
+
+            data_storage_mock
+        },
+        provider_expect: |mut provider_mock| {
+            provider_mock
+                .expect_get_weather()
+                .times(1)
+                .returning(|_, _| Ok(WeatherInfo::default()));
+            provider_mock
+        },
+        geocoder_expect: expect_kyiv,
+        cache_storage_expect: expect_empty_cache,
+        location_picker_expect: |location_picker_mock| location_picker_mock,
+    });
+
+    let processor = Processor::new(dependency_factory);
+
+    let get_weather_command = Cli {
+        command: WeatherCommand::Get {
+            address: AddressArgs::from("Kyiv"),
+            date: None,
+            provider_name: None,
+            units: UnitsArgs::default(),
+            output: OutputArgs::default(),
+            cache: CacheArgs::default(),
+        },
+    };
+
+    assert_eq!(Some(()), processor.run(get_weather_command).ok());
+}
+
+#[test]
+fn get_weather_ambiguous_provider() {
+    let dependency_factory = Box::new(TestableDependencyFactory {
+        data_storage_expect: |mut data_storage_mock| {
+            expect_default_locations(&mut data_storage_mock);
+
+            data_storage_mock
+                .expect_get_default_provider()
+                .times(1)
+                .returning(|| Ok(None));
+
+            data_storage_mock
+                .expect_get_all_providers()
+                .times(1)
+                .returning(|| {
+                    Ok(vec![
+                        WeatherProvider {
+                            name: "open".to_string(),
+                            provider: arguments::Provider::OpenWeather,
+                            api_key: Some("api_key".to_string()),
+                            contact: None,
+                        },
+                        WeatherProvider {
+                            name: "accu".to_string(),
+                            provider: arguments::Provider::AccuWeather,
+                            api_key: Some("api_key".to_string()),
+                            contact: None,
+                        },
+                    ])
+                });
+
+            data_storage_mock
+        },
+        provider_expect: |mut provider_mock| {
+            provider_mock.expect_get_weather().times(0);
+            provider_mock
+        },
+        geocoder_expect: |geocoder_mock| geocoder_mock,
+        cache_storage_expect: expect_empty_cache,
+        location_picker_expect: |location_picker_mock| location_picker_mock,
+    });
+
+    let processor = Processor::new(dependency_factory);
+
+    let get_weather_command = Cli {
+        command: WeatherCommand::Get {
+            address: AddressArgs::from("Kyiv"),
+            date: None,
+            provider_name: None,
+            units: UnitsArgs::default(),
+            output: OutputArgs::default(),
+            cache: CacheArgs::default(),
+        },
+    };
+
+    assert!(processor.run(get_weather_command).is_err());
+}
+
+#[test]
+fn get_weather_unknown_location() {
+    let dependency_factory = Box::new(TestableDependencyFactory {
+        data_storage_expect: |mut data_storage_mock| {
+            expect_default_locations(&mut data_storage_mock);
+
+            data_storage_mock
+                .expect_get_provider()
+                .with(eq("get_weather_unknown_location_test".to_string()))
+                .times(1)
+                .returning(|_| {
+                    Ok(WeatherProvider {
+                        name: "get_weather_unknown_location_test".to_string(),
+                        provider: arguments::Provider::OpenWeather,
+                        api_key: Some("api_key".to_string()),
+                        contact: None,
+                    })
+                });
+
+            data_storage_mock
+        },
+        provider_expect: |mut provider_mock| {
+            provider_mock.expect_get_weather().times(0);
+            provider_mock
+        },
+        geocoder_expect: |mut geocoder_mock| {
+            geocoder_mock
+                .expect_search()
+                .with(eq("Nowhere"))
+                .times(1)
+                .returning(|_| Ok(Vec::new()));
+            geocoder_mock
+        },
+        cache_storage_expect: expect_empty_cache,
+        location_picker_expect: |location_picker_mock| location_picker_mock,
+    });
+
+    let processor = Processor::new(dependency_factory);
+
+    let get_weather_command = Cli {
+        command: WeatherCommand::Get {
+            address: AddressArgs::from("Nowhere"),
+            date: None,
+            provider_name: Some("get_weather_unknown_location_test".to_string()),
+            units: UnitsArgs::default(),
+            output: OutputArgs::default(),
+            cache: CacheArgs::default(),
+        },
+    };
+
+    assert!(processor.run(get_weather_command).is_err());
+}
+
+#[test]
+fn get_weather_by_coordinates() {
+    let dependency_factory = Box::new(TestableDependencyFactory {
+        data_storage_expect: |mut data_storage_mock| {
+            expect_default_locations(&mut data_storage_mock);
+
+            data_storage_mock
+                .expect_get_provider()
+                .times(1)
+                .returning(|_| {
+                    Ok(WeatherProvider {
+                        name: "get_weather_by_coordinates_test".to_string(),
+                        provider: arguments::Provider::OpenWeather,
+                        api_key: Some("api_key".to_string()),
+                        contact: None,
+                    })
+                });
+
+            data_storage_mock
+        },
+        provider_expect: |mut provider_mock| {
+            provider_mock
+                .expect_get_weather()
+                .withf(|location, _| location.latitude == -33.87 && location.longitude == 151.21)
+                .times(1)
+                .returning(|_, _| Ok(WeatherInfo::default()));
+            provider_mock
+        },
+        geocoder_expect: |mut geocoder_mock| {
+            geocoder_mock.expect_search().times(0);
+            geocoder_mock
+                .expect_reverse()
+                .with(eq(-33.87), eq(151.21))
+                .times(1)
+                .returning(|_, _| {
+                    Ok(Some(Location {
+                        name: "Sydney".to_string(),
+                        country: Some("AU".to_string()),
+                        latitude: -33.87,
+                        longitude: 151.21,
+                        ..Location::default()
+                    }))
+                });
+            geocoder_mock
+        },
+        cache_storage_expect: expect_empty_cache,
+        location_picker_expect: |location_picker_mock| location_picker_mock,
+    });
+
+    let processor = Processor::new(dependency_factory);
+
+    let get_weather_command = Cli {
+        command: WeatherCommand::Get {
+            address: AddressArgs::from("-33.87,151.21"),
+            date: None,
+            provider_name: Some("get_weather_by_coordinates_test".to_string()),
+            units: UnitsArgs::default(),
+            output: OutputArgs::default(),
+            cache: CacheArgs::default(),
+        },
+    };
+
+    assert_eq!(Some(()), processor.run(get_weather_command).ok());
+}
+
+#[test]
+fn get_weather_by_coordinates_reverse_failed() {
+    let dependency_factory = Box::new(TestableDependencyFactory {
+        data_storage_expect: |mut data_storage_mock| {
+            expect_default_locations(&mut data_storage_mock);
+
+            data_storage_mock
+                .expect_get_provider()
+                .times(1)
+                .returning(|_| {
+                    Ok(WeatherProvider {
+                        name: "get_weather_by_coordinates_reverse_failed_test".to_string(),
+                        provider: arguments::Provider::OpenWeather,
+                        api_key: Some("api_key".to_string()),
+                        contact: None,
+                    })
+                });
+
+            data_storage_mock
+        },
+        provider_expect: |mut provider_mock| {
+            provider_mock
+                .expect_get_weather()
+                .times(1)
+                .returning(|_, _| Ok(WeatherInfo::default()));
+            provider_mock
+        },
+        geocoder_expect: |mut geocoder_mock| {
+            geocoder_mock
+                .expect_reverse()
+                .times(1)
+                .returning(|_, _| Err(WeatherError::Network("Service unavailable".to_string())));
+            geocoder_mock
+        },
+        cache_storage_expect: expect_empty_cache,
+        location_picker_expect: |location_picker_mock| location_picker_mock,
+    });
+
+    let processor = Processor::new(dependency_factory);
+
+    let get_weather_command = Cli {
+        command: WeatherCommand::Get {
+            address: AddressArgs::from("50.45,30.52"),
+            date: None,
+            provider_name: Some("get_weather_by_coordinates_reverse_failed_test".to_string()),
+            units: UnitsArgs::default(),
+            output: OutputArgs::default(),
+            cache: CacheArgs::default(),
+        },
+    };
+
+    assert_eq!(Some(()), processor.run(get_weather_command).ok());
+}
+
+#[test]
+fn get_weather_by_coordinates_failed() {
+    let dependency_factory = Box::new(TestableDependencyFactory {
+        data_storage_expect: |mut data_storage_mock| {
+            expect_default_locations(&mut data_storage_mock);
+
+            data_storage_mock
+                .expect_get_provider()
+                .times(1)
+                .returning(|_| {
+                    Ok(WeatherProvider {
+                        name: "get_weather_by_coordinates_failed_test".to_string(),
+                        provider: arguments::Provider::OpenWeather,
+                        api_key: Some("api_key".to_string()),
+                        contact: None,
+                    })
+                });
+
+            data_storage_mock
+        },
+        provider_expect: |mut provider_mock| {
+            provider_mock
+                .expect_get_weather()
+                .times(1)
+                .returning(|_, _| Err(WeatherError::Network("Service unavailable".to_string())));
+            provider_mock
+        },
+        geocoder_expect: |mut geocoder_mock| {
+            geocoder_mock.expect_reverse().times(0);
+            geocoder_mock
+        },
+        cache_storage_expect: expect_empty_cache,
+        location_picker_expect: |location_picker_mock| location_picker_mock,
+    });
+
+    let processor = Processor::new(dependency_factory);
+
+    let get_weather_command = Cli {
+        command: WeatherCommand::Get {
+            address: AddressArgs::from("50.45,30.52"),
+            date: None,
+            provider_name: Some("get_weather_by_coordinates_failed_test".to_string()),
+            units: UnitsArgs::default(),
+            output: OutputArgs::default(),
+            cache: CacheArgs::default(),
+        },
+    };
+
+    assert!(processor.run(get_weather_command).is_err());
+}
+
+#[test]
+fn get_forecast_by_postal_code() {
+    let dependency_factory = Box::new(TestableDependencyFactory {
+        data_storage_expect: |mut data_storage_mock| {
+            expect_default_locations(&mut data_storage_mock);
+
+            data_storage_mock
+                .expect_get_provider()
+                .times(1)
+                .returning(|_| {
+                    Ok(WeatherProvider {
+                        name: "get_forecast_by_postal_code_test".to_string(),
+                        provider: arguments::Provider::OpenWeather,
+                        api_key: Some("api_key".to_string()),
+                        contact: None,
+                    })
+                });
+
+            data_storage_mock
+        },
+        provider_expect: |mut provider_mock| {
+            provider_mock
+                .expect_get_forecast()
+                .with(eq(kyiv()), eq(5), eq(false))
+                .times(1)
+                .returning(|_, _, _| Ok(Forecast { entries: vec![] }));
+            provider_mock
+        },
+        geocoder_expect: |mut geocoder_mock| {
+            geocoder_mock
+                .expect_search_postal_code()
+                .withf(|code, country| code == "02000" && *country == Some("UA"))
+                .times(1)
+                .returning(|_, _| Ok(vec![kyiv()]));
+            geocoder_mock
+        },
+        cache_storage_expect: expect_empty_cache,
+        location_picker_expect: |location_picker_mock| location_picker_mock,
+    });
+
+    let processor = Processor::new(dependency_factory);
+
+    let get_forecast_command = Cli {
+        command: WeatherCommand::Forecast {
+            address: AddressArgs {
+                zip: Some("02000,UA".to_string()),
+                ..AddressArgs::default()
+            },
+            days: 5,
+            hourly: false,
+            provider_name: Some("get_forecast_by_postal_code_test".to_string()),
+            units: UnitsArgs::default(),
+            output: OutputArgs::default(),
+            cache: CacheArgs::default(),
+        },
+    };
+
+    assert_eq!(Some(()), processor.run(get_forecast_command).ok());
+}
+
+fn springfields() -> Vec<Location> {
+    vec![
+        Location {
+            name: "Springfield".to_string(),
+            country: Some("US".to_string()),
+            admin_area: Some("Illinois".to_string()),
+            latitude: 39.8,
+            longitude: -89.64,
+            ..Location::default()
+        },
+        Location {
+            name: "Springfield".to_string(),
+            country: Some("US".to_string()),
+            admin_area: Some("Missouri".to_string()),
+            latitude: 37.21,
+            longitude: -93.29,
+            ..Location::default()
+        },
+        Location {
+            name: "Springfield".to_string(),
+            country: Some("AU".to_string()),
+            admin_area: Some("Queensland".to_string()),
+            latitude: -27.67,
+            longitude: 152.91,
+            ..Location::default()
+        },
+    ]
+}
+
+fn springfield_provider(mut data_storage_mock: Box<MockDataStorage>) -> Box<MockDataStorage> {
+    expect_default_locations(&mut data_storage_mock);
+    data_storage_mock
+        .expect_get_provider()
+        .times(1)
+        .returning(|_| {
+            Ok(WeatherProvider {
+                name: "springfield_test".to_string(),
+                provider: arguments::Provider::AccuWeather,
+                api_key: Some("api_key".to_string()),
+                contact: None,
+            })
+        });
+    data_storage_mock
+}
+
+fn expect_springfields(mut geocoder_mock: Box<MockGeocoder>) -> Box<MockGeocoder> {
+    geocoder_mock
+        .expect_search()
+        .with(eq("Springfield"))
+        .times(1)
+        .returning(|_| Ok(springfields()));
+    geocoder_mock
+}
+
+fn get_springfield_command(country: Option<&str>, region: Option<&str>) -> Cli {
+    Cli {
+        command: WeatherCommand::Get {
+            address: AddressArgs {
+                country: country.map(|e| e.to_string()),
+                region: region.map(|e| e.to_string()),
+                ..AddressArgs::from("Springfield")
+            },
+            date: None,
+            provider_name: Some("springfield_test".to_string()),
+            units: UnitsArgs::default(),
+            output: OutputArgs::default(),
+            cache: CacheArgs::default(),
+        },
+    }
+}
+
+#[test]
+fn get_weather_ambiguous_location() {
+    let dependency_factory = Box::new(TestableDependencyFactory {
+        data_storage_expect: springfield_provider,
+        provider_expect: |mut provider_mock| {
+            provider_mock.expect_get_weather().times(0);
+            provider_mock
+        },
+        geocoder_expect: expect_springfields,
+        cache_storage_expect: expect_empty_cache,
+        location_picker_expect: |mut location_picker_mock| {
+            location_picker_mock
+                .expect_pick()
+                .withf(|address, locations| address == "Springfield" && locations.len() == 3)
+                .times(1)
+                .returning(|_, _| Ok(None));
+            location_picker_mock
+        },
+    });
+
+    let processor = Processor::new(dependency_factory);
+
+    let error = processor
+        .run(get_springfield_command(None, None))
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("Ambiguous location 'Springfield'"));
+    assert!(error.contains("Springfield, Queensland, AU"));
+}
+
+#[test]
+fn get_weather_picked_location() {
+    let dependency_factory = Box::new(TestableDependencyFactory {
+        data_storage_expect: springfield_provider,
+        provider_expect: |mut provider_mock| {
+            provider_mock
+                .expect_get_weather()
+                .withf(|location, _| location.admin_area.as_deref() == Some("Missouri"))
+                .times(1)
+                .returning(|_, _| Ok(WeatherInfo::default()));
+            provider_mock
+        },
+        geocoder_expect: expect_springfields,
+        cache_storage_expect: expect_empty_cache,
+        location_picker_expect: |mut location_picker_mock| {
+            location_picker_mock
+                .expect_pick()
+                .withf(|_, locations| locations.len() == 2)
+                .times(1)
+                .returning(|_, _| Ok(Some(1)));
+            location_picker_mock
+        },
+    });
+
+    let processor = Processor::new(dependency_factory);
+
+    assert_eq!(
+        Some(()),
+        processor
+            .run(get_springfield_command(Some("us"), None))
+            .ok()
+    );
+}
+
+#[test]
+fn get_weather_narrowed_location() {
+    let dependency_factory = Box::new(TestableDependencyFactory {
+        data_storage_expect: springfield_provider,
+        provider_expect: |mut provider_mock| {
+            provider_mock
+                .expect_get_weather()
+                .withf(|location, _| location.admin_area.as_deref() == Some("Illinois"))
+                .times(1)
+                .returning(|_, _| Ok(WeatherInfo::default()));
+            provider_mock
+        },
+        geocoder_expect: expect_springfields,
+        cache_storage_expect: expect_empty_cache,
+        location_picker_expect: |mut location_picker_mock| {
+            location_picker_mock.expect_pick().times(0);
+            location_picker_mock
+        },
+    });
+
+    let processor = Processor::new(dependency_factory);
+
+    assert_eq!(
+        Some(()),
+        processor
+            .run(get_springfield_command(Some("US"), Some("ill")))
+            .ok()
+    );
+}
+
+#[test]
+fn add_location() {
+    let dependency_factory = Box::new(TestableDependencyFactory {
+        data_storage_expect: |mut data_storage_mock| {
+            data_storage_mock
+                .expect_get_location()
+                .with(eq("office"))
+                .times(1)
+                .returning(|_| Err(WeatherError::NotFound("Not found".to_string())));
+
+            data_storage_mock
+                .expect_get_provider()
+                .with(eq("owm"))
+                .times(1)
+                .returning(|_| {
+                    Ok(WeatherProvider {
+                        name: "owm".to_string(),
+                        provider: arguments::Provider::OpenWeather,
+                        api_key: Some("api_key".to_string()),
+                        contact: None,
+                    })
+                });
+
+            data_storage_mock
+                .expect_add_location()
+                .withf(|e| {
+                    e.name == "office"
+                        && e.location.latitude == 50.45
+                        && e.location.longitude == 30.52
+                        && e.provider.as_deref() == Some("owm")
+                })
+                .times(1)
+                .returning(|_| Ok(()));
+
+            data_storage_mock
+        },
+        provider_expect: |provider_mock| provider_mock,
+        geocoder_expect: |mut geocoder_mock| {
+            geocoder_mock.expect_search().times(0);
+            geocoder_mock
+        },
+        cache_storage_expect: expect_empty_cache,
+        location_picker_expect: |location_picker_mock| location_picker_mock,
+    });
+
+    let processor = Processor::new(dependency_factory);
+
+    let add_location_command = Cli {
+        command: WeatherCommand::Location(LocationCommand {
+            command: LocationSubcommand::Add {
+                name: "office".to_string(),
+                address: AddressArgs {
+                    coords: Some("50.45,30.52".to_string()),
+                    ..AddressArgs::default()
+                },
+                provider: Some("owm".to_string()),
+            },
+        }),
+    };
+
+    assert_eq!(Some(()), processor.run(add_location_command).ok());
+}
+
+#[test]
+fn add_geocoded_location() {
+    let dependency_factory = Box::new(TestableDependencyFactory {
+        data_storage_expect: |mut data_storage_mock| {
+            data_storage_mock
+                .expect_get_location()
+                .with(eq("home"))
+                .times(1)
+                .returning(|_| Err(WeatherError::NotFound("Not found".to_string())));
+
+            data_storage_mock
+                .expect_get_geocoder()
+                .times(1)
+                .returning(|| Ok(GeocoderConfig::Provider));
+
+            data_storage_mock
+                .expect_get_default_provider()
+                .times(1)
+                .returning(|| Ok(None));
+
+            data_storage_mock
+                .expect_get_all_providers()
+                .times(1)
+                .returning(|| {
+                    Ok(vec![WeatherProvider {
+                        name: "owm".to_string(),
+                        provider: arguments::Provider::OpenWeather,
+                        api_key: Some("api_key".to_string()),
+                        contact: None,
+                    }])
+                });
+
+            data_storage_mock
+                .expect_add_location()
+                .with(eq(SavedLocation {
+                    name: "home".to_string(),
+                    location: kyiv(),
+                    provider: None,
+                }))
+                .times(1)
+                .returning(|_| Ok(()));
+
+            data_storage_mock
+        },
+        provider_expect: |provider_mock| provider_mock,
+        geocoder_expect: expect_kyiv,
+        cache_storage_expect: expect_empty_cache,
+        location_picker_expect: |location_picker_mock| location_picker_mock,
+    });
+
+    let processor = Processor::new(dependency_factory);
+
+    let add_location_command = Cli {
+        command: WeatherCommand::Location(LocationCommand {
+            command: LocationSubcommand::Add {
+                name: "home".to_string(),
+                address: AddressArgs::from("Kyiv"),
+                provider: None,
+            },
+        }),
+    };
+
+    assert_eq!(Some(()), processor.run(add_location_command).ok());
+}
+
+#[test]
+fn get_weather_saved_location() {
+    let dependency_factory = Box::new(TestableDependencyFactory {
+        data_storage_expect: |mut data_storage_mock| {
+            data_storage_mock
+                .expect_get_location()
+                .with(eq("office"))
+                .times(1)
+                .returning(|_| {
+                    Ok(SavedLocation {
+                        name: "office".to_string(),
+                        location: kyiv(),
+                        provider: Some("accu".to_string()),
+                    })
+                });
+
+            data_storage_mock
+                .expect_get_provider()
+                .with(eq("accu"))
+                .times(1)
+                .returning(|_| {
+                    Ok(WeatherProvider {
+                        name: "accu".to_string(),
+                        provider: arguments::Provider::AccuWeather,
+                        api_key: Some("api_key".to_string()),
+                        contact: None,
+                    })
+                });
+
+            data_storage_mock
+        },
+        provider_expect: |mut provider_mock| {
+            provider_mock
+                .expect_get_weather()
+                .with(eq(kyiv()), eq(None))
+                .times(1)
+                .returning(|_, _| Ok(WeatherInfo::default()));
+            provider_mock
+        },
+        geocoder_expect: |mut geocoder_mock| {
+            geocoder_mock.expect_search().times(0);
+            geocoder_mock
+        },
+        cache_storage_expect: expect_empty_cache,
+        location_picker_expect: |location_picker_mock| location_picker_mock,
+    });
+
+    let processor = Processor::new(dependency_factory);
+
+    let get_weather_command = Cli {
+        command: WeatherCommand::Get {
+            address: AddressArgs::from("office"),
+            date: None,
+            provider_name: None,
+            units: UnitsArgs::default(),
+            output: OutputArgs::default(),
+            cache: CacheArgs::default(),
+        },
+    };
+
+    assert_eq!(Some(()), processor.run(get_weather_command).ok());
+}
+
+/// Makes the processor getting Kyiv weather with the provider and cache expectations.
+fn cached_kyiv_processor(
+    geocoder_expect: fn(Box<MockGeocoder>) -> Box<MockGeocoder>,
+    provider_expect: fn(Box<MockProvider>) -> Box<MockProvider>,
+    cache_storage_expect: fn(Box<MockCacheStorage>) -> Box<MockCacheStorage>,
+) -> Processor {
+    Processor::new(Box::new(TestableDependencyFactory {
+        data_storage_expect: |mut data_storage_mock| {
+            expect_default_locations(&mut data_storage_mock);
+
+            data_storage_mock.expect_get_provider().returning(|_| {
+                Ok(WeatherProvider {
+                    name: "cache_test".to_string(),
+                    provider: arguments::Provider::OpenWeather,
+                    api_key: Some("api_key".to_string()),
+                    contact: None,
+                })
+            });
+
+            data_storage_mock
+        },
+        provider_expect,
+        geocoder_expect,
+        cache_storage_expect,
+        location_picker_expect: |location_picker_mock| location_picker_mock,
+    }))
+}
+
+/// Expects Kyiv to be searched, as it is not cached.
+fn expect_kyiv_search(mut geocoder_mock: Box<MockGeocoder>) -> Box<MockGeocoder> {
+    geocoder_mock
+        .expect_search()
+        .returning(|_| Ok(vec![kyiv()]));
+    geocoder_mock
+}
+
+fn get_kyiv_command(cache: CacheArgs) -> Cli {
+    Cli {
+        command: WeatherCommand::Get {
+            address: AddressArgs::from("Kyiv"),
+            date: None,
+            provider_name: Some("cache_test".to_string()),
+            units: UnitsArgs::default(),
+            output: OutputArgs::default(),
+            cache,
+        },
+    }
+}
+
+#[test]
+fn get_weather_cached() {
+    let processor = cached_kyiv_processor(
+        expect_kyiv_search,
+        |mut provider_mock| {
+            provider_mock.expect_get_weather().times(0);
+            provider_mock
+        },
+        |mut cache_storage_mock| {
+            cache_storage_mock
+                .expect_get()
+                .withf(|key| key.starts_with("search|cache_test|"))
+                .returning(|_| Ok(None));
+            cache_storage_mock
+                .expect_get()
+                .withf(|key| key.starts_with("weather|cache_test|50.4500,30.5200"))
+                .returning(|_| {
+                    Ok(Some(
+                        serde_json::json!({ "response": { "description": "Cached" } }),
+                    ))
+                });
+            cache_storage_mock.expect_put().returning(|_, _, _| Ok(()));
+            cache_storage_mock
+        },
+    );
+
+    assert_eq!(
+        Some(()),
+        processor.run(get_kyiv_command(CacheArgs::default())).ok()
+    );
+}
+
+#[test]
+fn get_weather_no_cache() {
+    let processor = cached_kyiv_processor(
+        expect_kyiv_search,
+        |mut provider_mock| {
+            provider_mock
+                .expect_get_weather()
+                .times(1)
+                .returning(|_, _| Ok(WeatherInfo::default()));
+            provider_mock
+        },
+        |mut cache_storage_mock| {
+            cache_storage_mock.expect_get().times(0);
+            cache_storage_mock.expect_put().times(0);
+            cache_storage_mock
+        },
+    );
+
+    let cache = CacheArgs {
+        no_cache: true,
+        ..CacheArgs::default()
+    };
+    assert_eq!(Some(()), processor.run(get_kyiv_command(cache)).ok());
+}
+
+#[test]
+fn get_weather_refresh_cache() {
+    static WEATHER_STORED: AtomicBool = AtomicBool::new(false);
+
+    let processor = cached_kyiv_processor(
+        expect_kyiv_search,
+        |mut provider_mock| {
+            provider_mock
+                .expect_get_weather()
+                .times(1)
+                .returning(|_, _| Ok(WeatherInfo::default()));
+            provider_mock
+        },
+        |mut cache_storage_mock| {
+            cache_storage_mock.expect_get().times(0);
+            cache_storage_mock
+                .expect_put()
+                .withf(|key, _, ttl| {
+                    key.starts_with("weather|") && *ttl == chrono::Duration::minutes(10)
+                })
+                .returning(|_, _, _| {
+                    WEATHER_STORED.store(true, Ordering::SeqCst);
+                    Ok(())
+                });
+            cache_storage_mock
+                .expect_put()
+                .withf(|key, _, _| key.starts_with("search|"))
+                .returning(|_, _, _| Ok(()));
+            cache_storage_mock
+        },
+    );
+
+    let cache = CacheArgs {
+        refresh: true,
+        ..CacheArgs::default()
+    };
+    assert_eq!(Some(()), processor.run(get_kyiv_command(cache)).ok());
+    assert!(WEATHER_STORED.load(Ordering::SeqCst));
+}
+
+#[test]
+fn clear_cache() {
+    let dependency_factory = Box::new(TestableDependencyFactory {
+        data_storage_expect: |data_storage_mock| data_storage_mock,
+        provider_expect: |provider_mock| provider_mock,
+        geocoder_expect: |geocoder_mock| geocoder_mock,
+        cache_storage_expect: |mut cache_storage_mock| {
+            cache_storage_mock
+                .expect_clear()
+                .times(1)
+                .returning(|| Ok(3));
+            cache_storage_mock
+        },
+        location_picker_expect: |location_picker_mock| location_picker_mock,
+    });
+
+    let processor = Processor::new(dependency_factory);
+
+    let clear_cache_command = Cli {
+        command: WeatherCommand::Cache(CacheCommand {
+            command: CacheSubcommand::Clear { provider: None },
+        }),
+    };
+
+    assert_eq!(Some(()), processor.run(clear_cache_command).ok());
+}
+
+#[test]
+fn clear_provider_cache() {
+    let dependency_factory = Box::new(TestableDependencyFactory {
+        data_storage_expect: |mut data_storage_mock| {
+            data_storage_mock
+                .expect_get_provider()
+                .with(eq("accu"))
+                .times(1)
+                .returning(|_| {
+                    Ok(WeatherProvider {
+                        name: "accu".to_string(),
+                        provider: arguments::Provider::AccuWeather,
+                        api_key: Some("api_key".to_string()),
+                        contact: None,
+                    })
+                });
+            data_storage_mock
+        },
+        provider_expect: |provider_mock| provider_mock,
+        geocoder_expect: |geocoder_mock| geocoder_mock,
+        cache_storage_expect: |mut cache_storage_mock| {
+            cache_storage_mock.expect_clear().times(0);
+            cache_storage_mock
+                .expect_clear_scope()
+                .with(eq("accu"))
+                .times(1)
+                .returning(|_| Ok(3));
+            cache_storage_mock
+                .expect_clear_scope()
+                .with(eq("accu-weather"))
+                .times(1)
+                .returning(|_| Ok(2));
+            cache_storage_mock
+        },
+        location_picker_expect: |location_picker_mock| location_picker_mock,
+    });
+
+    let processor = Processor::new(dependency_factory);
+
+    let clear_cache_command = Cli {
+        command: WeatherCommand::Cache(CacheCommand {
+            command: CacheSubcommand::Clear {
+                provider: Some("accu".to_string()),
+            },
+        }),
+    };
+
+    assert_eq!(Some(()), processor.run(clear_cache_command).ok());
+}
+
+#[test]
+fn get_weather_offline() {
+    let processor = cached_kyiv_processor(
+        |mut geocoder_mock| {
+            // Locations are resolved from the cache as well.
+            geocoder_mock.expect_search().times(0);
+            geocoder_mock
+        },
+        |mut provider_mock| {
+            provider_mock.expect_get_weather().times(0);
+            provider_mock
+        },
+        |mut cache_storage_mock| {
+            cache_storage_mock.expect_get().returning(|_| Ok(None));
+            cache_storage_mock.expect_get_last().returning(|key| {
+                let value = match key.starts_with("search|") {
+                    true => serde_json::to_value(vec![kyiv()]).unwrap(),
+                    false => serde_json::json!({ "response": { "description": "Stored" } }),
+                };
+                Ok(Some(CachedValue {
+                    value,
+                    stored_at: Utc::now() - chrono::Duration::hours(3),
+                }))
+            });
+            cache_storage_mock.expect_put().times(0);
+            cache_storage_mock
+        },
+    );
+
+    let cache = CacheArgs {
+        offline: true,
+        ..CacheArgs::default()
+    };
+    assert_eq!(Some(()), processor.run(get_kyiv_command(cache)).ok());
+    assert!(processor.served_stale());
+}
+
+/// Gets the provider config by name.
+fn provider_config(name: &str) -> WeatherProvider {
+    WeatherProvider {
+        name: name.to_string(),
+        provider: arguments::Provider::OpenWeather,
+        api_key: Some("api_key".to_string()),
+        contact: None,
+    }
+}
+
+#[test]
+fn add_group() {
+    let dependency_factory = Box::new(TestableDependencyFactory {
+        data_storage_expect: |mut data_storage_mock| {
+            data_storage_mock
+                .expect_get_provider()
+                .returning(|name| match name {
+                    "owm" | "accu" => Ok(provider_config(name)),
+                    _ => Err(WeatherError::NotFound("Not found".to_string())),
+                });
+            data_storage_mock
+                .expect_get_group()
+                .returning(|_| Err(WeatherError::NotFound("Not found".to_string())));
+
+            data_storage_mock
+                .expect_add_group()
+                .withf(|g| g.name == "primary" && g.providers == ["owm", "accu"])
+                .returning(|_| Ok(()));
+
+            data_storage_mock
+        },
+        provider_expect: |provider_mock| provider_mock,
+        geocoder_expect: |geocoder_mock| geocoder_mock,
+        cache_storage_expect: expect_empty_cache,
+        location_picker_expect: |location_picker_mock| location_picker_mock,
+    });
+
+    let processor = Processor::new(dependency_factory);
+
+    let add_group_command = |name: &str, providers: &[&str]| Cli {
+        command: WeatherCommand::Group(GroupCommand {
+            command: GroupSubcommand::Add(ProviderGroup {
+                name: name.to_string(),
+                providers: providers.iter().map(|p| p.to_string()).collect(),
+            }),
+        }),
+    };
+
+    assert_eq!(
+        Some(()),
+        processor
+            .run(add_group_command("primary", &["owm", "accu"]))
+            .ok()
+    );
+    // Groups share the namespace with providers and consist of configured providers only.
+    assert!(processor.run(add_group_command("owm", &["accu"])).is_err());
+    assert!(processor
+        .run(add_group_command("secondary", &["owm", "unknown"]))
+        .is_err());
+}
+
+#[test]
+fn get_weather_group() {
+    static FIRST_MADE: AtomicBool = AtomicBool::new(false);
+
+    let dependency_factory = Box::new(TestableDependencyFactory {
+        data_storage_expect: |mut data_storage_mock| {
+            expect_default_locations(&mut data_storage_mock);
+
+            data_storage_mock
+                .expect_get_provider()
+                .returning(|name| match name {
+                    "owm" | "accu" => Ok(provider_config(name)),
+                    _ => Err(WeatherError::NotFound("Not found".to_string())),
+                });
+            data_storage_mock
+                .expect_get_group()
+                .with(eq("primary".to_string()))
+                .returning(|_| {
+                    Ok(ProviderGroup {
+                        name: "primary".to_string(),
+                        providers: vec!["owm".to_string(), "accu".to_string()],
+                    })
+                });
+
+            data_storage_mock
+        },
+        provider_expect: |mut provider_mock| {
+            // The first provider of the group cannot be reached.
+            match FIRST_MADE.swap(true, Ordering::SeqCst) {
+                false => provider_mock
+                    .expect_get_weather()
+                    .returning(|_, _| Err(WeatherError::Network("no connectivity".to_string()))),
+                true => provider_mock
+                    .expect_get_weather()
+                    .returning(|_, _| Ok(WeatherInfo::default())),
+            };
+            provider_mock
+        },
+        geocoder_expect: |mut geocoder_mock| {
+            geocoder_mock
+                .expect_search()
+                .with(eq("Kyiv"))
+                .returning(|_| Ok(vec![kyiv()]));
+            geocoder_mock
+        },
+        cache_storage_expect: expect_empty_cache,
+        location_picker_expect: |location_picker_mock| location_picker_mock,
+    });
+
+    let processor = Processor::new(dependency_factory);
+
+    let get_weather_command = Cli {
+        command: WeatherCommand::Get {
+            address: AddressArgs::from("Kyiv"),
+            date: None,
+            provider_name: Some("primary".to_string()),
+            units: UnitsArgs::default(),
+            output: OutputArgs::default(),
+            cache: CacheArgs::default(),
+        },
+    };
+
+    assert_eq!(Some(()), processor.run(get_weather_command).ok());
+}
+
+/// Makes the processor comparing two configured providers for Kyiv.
+fn compare_processor(provider_expect: fn(Box<MockProvider>) -> Box<MockProvider>) -> Processor {
+    let dependency_factory = Box::new(TestableDependencyFactory {
+        data_storage_expect: |mut data_storage_mock| {
+            expect_default_locations(&mut data_storage_mock);
+
+            data_storage_mock
+                .expect_get_all_providers()
+                .times(1)
+                .returning(|| Ok(vec![provider_config("owm"), provider_config("accu")]));
+
+            data_storage_mock
+        },
+        provider_expect,
+        geocoder_expect: |mut geocoder_mock| {
+            geocoder_mock
+                .expect_search()
+                .with(eq("Kyiv"))
+                .returning(|_| Ok(vec![kyiv()]));
+            geocoder_mock
+        },
+        cache_storage_expect: expect_empty_cache,
+        location_picker_expect: |location_picker_mock| location_picker_mock,
+    });
+
+    Processor::new(dependency_factory)
+}
+
+fn compare_kyiv_command() -> Cli {
+    Cli {
+        command: WeatherCommand::Compare {
+            address: AddressArgs::from("Kyiv"),
+            date: None,
+            providers: vec![],
+            units: UnitsArgs::default(),
+        },
+    }
+}
+
+#[test]
+fn compare_providers() {
+    let processor = compare_processor(|mut provider_mock| {
+        provider_mock
+            .expect_get_weather()
+            .times(1)
+            .returning(|_, _| Ok(WeatherInfo::default()));
+        provider_mock
+    });
+
+    assert_eq!(Some(()), processor.run(compare_kyiv_command()).ok());
+}
+
+#[test]
+fn compare_providers_failed() {
+    let processor = compare_processor(|mut provider_mock| {
+        provider_mock
+            .expect_get_weather()
+            .times(1)
+            .returning(|_, _| {
+                Err(WeatherError::Auth(
+                    "Request failed (401 Unauthorized)".to_string(),
+                ))
+            });
+        provider_mock
+    });
+
+    assert!(processor.run(compare_kyiv_command()).is_err());
+}
@@ -0,0 +1,189 @@
+use weather::provider::units::{
+    Length, LengthUnit, Pressure, PressureUnit, Quantity, Speed, SpeedUnit, Temperature,
+    TemperatureUnit, UnitSystem, Units,
+};
+use weather::provider::{FallbackProvider, Forecast, Provider, WeatherInfo};
+
+use chrono::{DateTime, Utc};
+use mockall::mock;
+use reqwest::StatusCode;
+use weather::error::WeatherError;
+use weather::geocoder::Location;
+
+mock! {
+    Provider {}
+    impl Provider for Provider {
+        fn get_weather(
+            &self,
+            location: &Location,
+            date: Option<DateTime<Utc>>,
+        ) -> Result<WeatherInfo, WeatherError>;
+        fn get_forecast(
+            &self,
+            location: &Location,
+            days: u32,
+            hourly: bool,
+        ) -> Result<Forecast, WeatherError>;
+    }
+}
+
+#[test]
+fn display_weather_info() {
+    let info = WeatherInfo {
+        description: Some("Clouds".to_string()),
+        temperature: Some(Temperature::from_celsius(1.5)),
+        pressure: Some(Pressure::from_hectopascals(1013.0)),
+        wind_speed: Some(Speed::from_meters_per_second(3.2)),
+        wind_direction: Some(270),
+        precipitation_probability: Some(40),
+        ..WeatherInfo::default()
+    };
+
+    assert_eq!(
+        "Clouds\n-> Temperature: 1.5°C\n-> Humidity: unknown\n-> Pressure: 1013 hPa\
+         \n-> Wind: 3.2 m/s from 270°\n-> Precipitation probability: 40%",
+        info.to_string()
+    );
+
+    assert_eq!(
+        "Clouds\n-> Temperature: 34.7°F\n-> Humidity: unknown\n-> Pressure: 29.91 inHg\
+         \n-> Wind: 7.2 mph from 270°\n-> Precipitation probability: 40%",
+        info.display(UnitSystem::Imperial.into()).to_string()
+    );
+}
+
+#[test]
+fn display_air_quality() {
+    let info = WeatherInfo {
+        description: Some("Haze".to_string()),
+        air_quality: Some(3),
+        ..WeatherInfo::default()
+    };
+
+    assert_eq!(
+        "Haze\n-> Temperature: unknown\n-> Humidity: unknown\n-> Pressure: unknown\
+         \n-> Air quality: Unhealthy for sensitive groups (US EPA index 3)",
+        info.to_string()
+    );
+}
+
+#[test]
+fn convert_units() {
+    let temperature = Temperature::from_fahrenheit(212.0);
+    assert_eq!("100°C", temperature.format(TemperatureUnit::Celsius));
+    assert_eq!("373.1 K", temperature.format(TemperatureUnit::Kelvin));
+
+    let pressure = Pressure::from_inches_of_mercury(29.92);
+    assert_eq!("1013 hPa", pressure.format(PressureUnit::Hectopascal));
+    assert_eq!("101321 Pa", pressure.format(PressureUnit::Pascal));
+    assert_eq!(
+        "760 mmHg",
+        pressure.format(PressureUnit::MillimeterOfMercury)
+    );
+
+    let speed = Speed::from_kilometers_per_hour(36.0);
+    assert_eq!("10 m/s", speed.format(SpeedUnit::MetersPerSecond));
+    assert_eq!("19.4 kn", speed.format(SpeedUnit::Knots));
+
+    let length = Length::from_millimeters(25.4);
+    assert_eq!("1 in", length.format(LengthUnit::Inch));
+    assert_eq!("0.0254 m", length.format(LengthUnit::Meter));
+    assert_eq!("1 mi", Length::from_miles(1.0).format(LengthUnit::Mile));
+}
+
+#[test]
+fn unit_systems() {
+    let units = Units::from(UnitSystem::Si);
+    assert_eq!(TemperatureUnit::Kelvin, units.temperature);
+    assert_eq!(PressureUnit::Pascal, units.pressure);
+    assert_eq!(LengthUnit::Meter, units.precipitation);
+
+    assert_eq!(Units::from(UnitSystem::Metric), Units::default());
+}
+
+/// Makes the provider mock answering the weather request with the result.
+fn answering(result: fn() -> Result<WeatherInfo, WeatherError>) -> Box<dyn Provider> {
+    let mut provider_mock = MockProvider::new();
+    provider_mock
+        .expect_get_weather()
+        .times(1)
+        .returning(move |_, _| result());
+    Box::new(provider_mock)
+}
+
+/// Makes the provider mock not expecting any requests.
+fn not_requested() -> Box<dyn Provider> {
+    let mut provider_mock = MockProvider::new();
+    provider_mock.expect_get_weather().times(0);
+    Box::new(provider_mock)
+}
+
+#[test]
+fn fallback_on_transient_errors() {
+    let provider = FallbackProvider::new(vec![
+        (
+            "offline".to_string(),
+            answering(|| Err(WeatherError::Network("no connectivity".to_string()))),
+        ),
+        (
+            "unavailable".to_string(),
+            answering(|| Err(WeatherError::from_status(StatusCode::SERVICE_UNAVAILABLE))),
+        ),
+        (
+            "exhausted".to_string(),
+            answering(|| Err(WeatherError::from_status(StatusCode::TOO_MANY_REQUESTS))),
+        ),
+        (
+            "working".to_string(),
+            answering(|| Ok(WeatherInfo::default())),
+        ),
+        ("spare".to_string(), not_requested()),
+    ]);
+    assert_eq!(None, provider.answered());
+
+    assert!(provider.get_weather(&Location::default(), None).is_ok());
+    assert_eq!(Some("working".to_string()), provider.answered());
+}
+
+#[test]
+fn fallback_stops_on_other_errors() {
+    let provider = FallbackProvider::new(vec![
+        (
+            "unauthorized".to_string(),
+            answering(|| Err(WeatherError::from_status(StatusCode::UNAUTHORIZED))),
+        ),
+        ("spare".to_string(), not_requested()),
+    ]);
+    assert!(provider.get_weather(&Location::default(), None).is_err());
+    assert_eq!(None, provider.answered());
+
+    let provider = FallbackProvider::new(vec![
+        (
+            "broken".to_string(),
+            answering(|| Err(WeatherError::Parse("Failed to parse response".to_string()))),
+        ),
+        ("spare".to_string(), not_requested()),
+    ]);
+    assert!(provider.get_weather(&Location::default(), None).is_err());
+}
+
+#[test]
+fn fallback_returns_last_error() {
+    let provider = FallbackProvider::new(vec![
+        (
+            "offline".to_string(),
+            answering(|| Err(WeatherError::Network("no connectivity".to_string()))),
+        ),
+        (
+            "unavailable".to_string(),
+            answering(|| Err(WeatherError::from_status(StatusCode::BAD_GATEWAY))),
+        ),
+    ]);
+
+    let error = match provider.get_weather(&Location::default(), None) {
+        Ok(_) => panic!("all providers failed"),
+        Err(e) => e,
+    };
+    assert_eq!("Request failed (502 Bad Gateway)", error.to_string());
+    assert_eq!(None, provider.answered());
+}